@@ -0,0 +1,355 @@
+//the base integer instructions of RV32I shared by the benchmarks. All opcodes are 32 bits wide,
+//so the decoder forks on bits of all four bytes.
+macro_rules! rv32i {
+    ($(#[$attr:meta])*) => {
+        #[derive(InstructionSet, Debug)]
+        $(#[$attr])*
+        pub enum Rv32i {
+            #[opcode = "0bjjjjjjjjjjjjjjjjjjjj_rrrrr_0110111"]
+            Lui {
+                #[variable = "j"]
+                imm: u32,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bjjjjjjjjjjjjjjjjjjjj_rrrrr_0010111"]
+            Auipc {
+                #[variable = "j"]
+                imm: u32,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bjjjjjjjjjjjjjjjjjjjj_rrrrr_1101111"]
+            Jal {
+                #[variable = "j"]
+                imm: u32,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_000_rrrrr_1100111"]
+            Jalr {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_000_lllll_1100011"]
+            Beq {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_001_lllll_1100011"]
+            Bne {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_100_lllll_1100011"]
+            Blt {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_101_lllll_1100011"]
+            Bge {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_110_lllll_1100011"]
+            Bltu {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_111_lllll_1100011"]
+            Bgeu {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_000_rrrrr_0000011"]
+            Lb {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_001_rrrrr_0000011"]
+            Lh {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_010_rrrrr_0000011"]
+            Lw {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_100_rrrrr_0000011"]
+            Lbu {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_101_rrrrr_0000011"]
+            Lhu {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_000_lllll_0100011"]
+            Sb {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_001_lllll_0100011"]
+            Sh {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bhhhhhhh_ttttt_sssss_010_lllll_0100011"]
+            Sw {
+                #[variable = "h"]
+                imm_hi: u8,
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "l"]
+                imm_lo: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_000_rrrrr_0010011"]
+            Addi {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_010_rrrrr_0010011"]
+            Slti {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_011_rrrrr_0010011"]
+            Sltiu {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_100_rrrrr_0010011"]
+            Xori {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_110_rrrrr_0010011"]
+            Ori {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0bmmmmmmmmmmmm_sssss_111_rrrrr_0010011"]
+            Andi {
+                #[variable = "m"]
+                imm: u16,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_nnnnn_sssss_001_rrrrr_0010011"]
+            Slli {
+                #[variable = "n"]
+                shamt: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_nnnnn_sssss_101_rrrrr_0010011"]
+            Srli {
+                #[variable = "n"]
+                shamt: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0100000_nnnnn_sssss_101_rrrrr_0010011"]
+            Srai {
+                #[variable = "n"]
+                shamt: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_000_rrrrr_0110011"]
+            Add {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0100000_ttttt_sssss_000_rrrrr_0110011"]
+            Sub {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_001_rrrrr_0110011"]
+            Sll {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_010_rrrrr_0110011"]
+            Slt {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_011_rrrrr_0110011"]
+            Sltu {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_100_rrrrr_0110011"]
+            Xor {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_101_rrrrr_0110011"]
+            Srl {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0100000_ttttt_sssss_101_rrrrr_0110011"]
+            Sra {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_110_rrrrr_0110011"]
+            Or {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+            #[opcode = "0b0000000_ttttt_sssss_111_rrrrr_0110011"]
+            And {
+                #[variable = "t"]
+                rs2: u8,
+                #[variable = "s"]
+                rs1: u8,
+                #[variable = "r"]
+                rd: u8,
+            },
+        }
+    };
+}
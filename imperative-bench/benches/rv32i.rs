@@ -0,0 +1,117 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use imperative_rs::InstructionSet;
+
+include!("isa/rv32i.rs");
+
+mod tree {
+    use imperative_rs::InstructionSet;
+    rv32i!();
+}
+
+mod table {
+    use imperative_rs::InstructionSet;
+    rv32i!(#[imperative(decoder = "table")]);
+}
+
+fn program() -> Vec<u8> {
+    //a mix of all formats, so the decoder takes every path through the opcode
+    use tree::Rv32i::*;
+    let instructions = [
+        Lui {
+            imm: 0x12345,
+            rd: 1,
+        },
+        Addi {
+            imm: 0x678,
+            rs1: 1,
+            rd: 1,
+        },
+        Lw {
+            imm: 8,
+            rs1: 2,
+            rd: 3,
+        },
+        Add {
+            rs2: 3,
+            rs1: 1,
+            rd: 4,
+        },
+        Sub {
+            rs2: 1,
+            rs1: 4,
+            rd: 5,
+        },
+        Srai {
+            shamt: 3,
+            rs1: 5,
+            rd: 5,
+        },
+        Sw {
+            imm_hi: 0,
+            rs2: 5,
+            rs1: 2,
+            imm_lo: 12,
+        },
+        Bne {
+            imm_hi: 0x7f,
+            rs2: 0,
+            rs1: 5,
+            imm_lo: 0x19,
+        },
+        Xor {
+            rs2: 4,
+            rs1: 3,
+            rd: 6,
+        },
+        Jal {
+            imm: 0xff9ff,
+            rd: 0,
+        },
+    ];
+    let mut program = vec![0u8; 4 * 1024 * instructions.len()];
+    for (instr, buf) in instructions.iter().cycle().zip(program.chunks_mut(4)) {
+        instr
+            .encode(buf)
+            .unwrap_or_else(|_| panic!("Failed to encode instruction: {:?}", instr));
+    }
+    program
+}
+
+fn rv32i_benches(c: &mut Criterion) {
+    let program = program();
+
+    c.bench_function("decoding rv32i program", |b| {
+        b.iter(|| {
+            let mut pc = 0;
+            while pc < program.len() {
+                let (num_bytes, _) = tree::Rv32i::decode(&program[pc..]).unwrap_or_else(|_| {
+                    panic!(
+                        "Failed to decode instruction at {}: {:x?}",
+                        pc,
+                        &program[pc..pc + 4]
+                    )
+                });
+                pc += num_bytes;
+            }
+        })
+    });
+
+    c.bench_function("decoding rv32i program with table decoder", |b| {
+        b.iter(|| {
+            let mut pc = 0;
+            while pc < program.len() {
+                let (num_bytes, _) = table::Rv32i::decode(&program[pc..]).unwrap_or_else(|_| {
+                    panic!(
+                        "Failed to decode instruction at {}: {:x?}",
+                        pc,
+                        &program[pc..pc + 4]
+                    )
+                });
+                pc += num_bytes;
+            }
+        })
+    });
+}
+
+criterion_group!(benches, rv32i_benches);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+//! Reports the size of the machine code generated for the chip8 and rv32i decoders. The decoders are
+//! compiled like the other benchmarks (i.e. with optimizations) and the sizes of their functions
+//! are read from the symbol table of this executable. Run it with
+//! `cargo bench -p imperative-bench --bench size`.
+use imperative_rs::{DecodeError, InstructionSet};
+use object::{Object, ObjectSymbol, SymbolKind};
+use std::hint::black_box;
+
+include!("isa/chip8.rs");
+include!("isa/rv32i.rs");
+
+mod tree_speed {
+    use imperative_rs::InstructionSet;
+    chip8!();
+}
+
+mod tree_size {
+    use imperative_rs::InstructionSet;
+    chip8!(#[imperative(optimize = "size")]);
+}
+
+mod table_speed {
+    use imperative_rs::InstructionSet;
+    chip8!(#[imperative(decoder = "table")]);
+}
+
+mod table_size {
+    use imperative_rs::InstructionSet;
+    chip8!(#[imperative(decoder = "table", optimize = "size")]);
+}
+
+mod rv32i_tree {
+    use imperative_rs::InstructionSet;
+    rv32i!();
+}
+
+mod rv32i_table {
+    use imperative_rs::InstructionSet;
+    rv32i!(#[imperative(decoder = "table")]);
+}
+
+type Decoder<T> = fn(&[u8]) -> Result<(usize, T), DecodeError>;
+
+fn keep<T>(decode: Decoder<T>) {
+    //calling the decoder through a pointer keeps it from being inlined into main
+    let _ = black_box(decode)(black_box(&[0x00, 0xe0]));
+}
+
+fn main() {
+    keep(tree_speed::Chip8::decode);
+    keep(tree_size::Chip8::decode);
+    keep(table_speed::Chip8::decode);
+    keep(table_size::Chip8::decode);
+    keep(rv32i_tree::Rv32i::decode);
+    keep(rv32i_table::Rv32i::decode);
+
+    let exe = std::env::current_exe().expect("Couldn't locate the benchmark executable");
+    let data = std::fs::read(&exe).expect("Couldn't read the benchmark executable");
+    let file = object::File::parse(&*data).expect("Couldn't parse the benchmark executable");
+    let decoders = [
+        ("chip8", "tree_speed", "Chip8"),
+        ("chip8", "tree_size", "Chip8"),
+        ("chip8", "table_speed", "Chip8"),
+        ("chip8", "table_size", "Chip8"),
+        ("rv32i", "rv32i_tree", "Rv32i"),
+        ("rv32i", "rv32i_table", "Rv32i"),
+    ];
+    let mut sizes = [0u64; 6];
+    for symbol in file
+        .symbols()
+        .filter(|symbol| symbol.kind() == SymbolKind::Text)
+    {
+        let name = match symbol.name() {
+            Ok(name) => format!("{:#}", rustc_demangle::demangle(name)),
+            Err(_) => continue,
+        };
+        //the decoder itself and the closures of its shared arms
+        for ((_, module, set), size) in decoders.iter().zip(sizes.iter_mut()) {
+            if name.contains(&format!(
+                "::{}::{} as imperative_rs::InstructionSet>::decode",
+                module, set
+            )) {
+                *size += symbol.size();
+            }
+        }
+    }
+    for ((isa, module, _), size) in decoders.iter().zip(sizes.iter()) {
+        println!("{} decoder {:<12} {:>6} bytes", isa, module, size);
+    }
+}
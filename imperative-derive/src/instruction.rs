@@ -1,7 +1,9 @@
 use proc_macro::TokenStream;
+use proc_macro2::Literal;
 use proc_macro2::Span;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::quote;
+use proc_macro2::TokenTree;
+use quote::{quote, ToTokens};
 use std::collections::HashMap;
 use std::mem;
 use syn::parse::{Parse, ParseStream};
@@ -9,8 +11,8 @@ use syn::spanned::Spanned;
 use syn::token::{Brace, Paren};
 use syn::Result as SynResult;
 use syn::{
-    parse_quote, Attribute, Error, FieldsNamed, FieldsUnnamed, Ident, LitInt, LitStr, Token, Type,
-    Visibility,
+    parse_quote, Attribute, Error, Expr, Field, FieldsNamed, FieldsUnnamed, Ident, Lit, LitInt,
+    LitStr, Meta, MetaNameValue, Token, Type, Visibility,
 };
 
 lazy_static! {
@@ -55,25 +57,15 @@ fn is_supported_type(ty: &Type) -> bool {
 
 fn size_of(ty: &Type) -> usize {
     //! Returns the number of bits the input type fills
-    if *ty == parse_quote!(u8) {
+    if *ty == parse_quote!(u8) || *ty == parse_quote!(i8) {
         8
-    } else if *ty == parse_quote!(i8) {
-        8
-    } else if *ty == parse_quote!(u16) {
-        16
-    } else if *ty == parse_quote!(i16) {
+    } else if *ty == parse_quote!(u16) || *ty == parse_quote!(i16) {
         16
-    } else if *ty == parse_quote!(u32) {
-        32
-    } else if *ty == parse_quote!(i32) {
+    } else if *ty == parse_quote!(u32) || *ty == parse_quote!(i32) {
         32
-    } else if *ty == parse_quote!(u64) {
+    } else if *ty == parse_quote!(u64) || *ty == parse_quote!(i64) {
         64
-    } else if *ty == parse_quote!(i64) {
-        64
-    } else if *ty == parse_quote!(u128) {
-        128
-    } else if *ty == parse_quote!(i128) {
+    } else if *ty == parse_quote!(u128) || *ty == parse_quote!(i128) {
         128
     } else if *ty == parse_quote!(usize) {
         8 * mem::size_of::<usize>()
@@ -82,10 +74,7 @@ fn size_of(ty: &Type) -> usize {
     } else if *ty == parse_quote!(bool) {
         1
     } else {
-        panic!(format!(
-            "Unexpected type {:?} when getting memory size.",
-            ty
-        ));
+        panic!("Unexpected type {:?} when getting memory size.", ty);
     }
 }
 
@@ -95,25 +84,140 @@ pub(crate) enum Instruction {
 }
 
 impl Instruction {
-    pub(crate) fn encoder_block(&self) -> TokenStream2 {
+    pub(crate) fn encoder_block(&self, word: &Word) -> TokenStream2 {
+        match self {
+            Instruction::WithVars(instr) => instr.encoder_block(word),
+            Instruction::Unit(instr) => instr.encoder_block(word),
+        }
+    }
+
+    pub(crate) fn decoder_block(&self, word: &Word) -> TokenStream2 {
         match self {
-            Instruction::WithVars(instr) => instr.encoder_block(),
-            Instruction::Unit(instr) => instr.encoder_block(),
+            Instruction::WithVars(instr) => instr.decoder_block(word),
+            Instruction::Unit(instr) => instr.decoder_block(word),
+        }
+    }
+
+    pub(crate) fn decoder(&self, word: &Word) -> TokenStream2 {
+        //! Constructs the complete leaf of the matcher for this instruction. If the memory doesn't
+        //! contain this instruction's opcode the leaf evaluates to `DecodeError::UnknownOpcode`.
+        //! If the memory ends within a matching opcode, it evaluates to `DecodeError::UnexpectedEOF`
+        //! so callers know that more memory might still complete the instruction.
+        let block = self.decoder_block(word);
+        let truncated = self.opcode().truncated_condition(word);
+        quote! {
+            if #truncated {
+                Err(imperative_rs::DecodeError::UnexpectedEOF)
+            } else #block else {
+                Err(imperative_rs::DecodeError::UnknownOpcode)
+            }
+        }
+    }
+
+    pub(crate) fn length_decoder(&self, word: &Word) -> TokenStream2 {
+        //! Constructs the leaf of the matcher for `instruction_len(..)`. Only the fields that are
+        //! needed to find the length of the trailing fields are decoded.
+        let block = match self {
+            Instruction::WithVars(instr) => instr.length_block(word),
+            Instruction::Unit(instr) => {
+                let num_bytes = instr.opcode.length(word);
+                let conditions = instr.opcode.build_match_conditions(word);
+                quote! { if #conditions { Ok(#num_bytes) } }
+            }
+        };
+        let truncated = self.opcode().truncated_condition(word);
+        quote! {
+            if #truncated {
+                Err(imperative_rs::DecodeError::UnexpectedEOF)
+            } else #block else {
+                Err(imperative_rs::DecodeError::UnknownOpcode)
+            }
+        }
+    }
+
+    pub(crate) fn min_len(&self, word: &Word) -> TokenStream2 {
+        //! Returns a constant expression for the least number of bytes this instruction can
+        //! occupy.
+        let num_bytes = self.opcode().length(word);
+        let trailing = self.trailing().iter().map(|field| field.min_len());
+        quote! { #num_bytes #(+ #trailing)* }
+    }
+
+    pub(crate) fn encoded_len_arm(&self, word: &Word) -> TokenStream2 {
+        //! Constructs the match arm of `encoded_len(&self)`. Only the trailing fields are bound,
+        //! all other fields are encoded in the opcode.
+        let ident = self.ident();
+        let num_bytes = self.opcode().length(word);
+        let fields = self.trailing().iter().map(|field| field.ident());
+        let trailing = self.trailing().iter().map(|field| field.encoded_len());
+        quote! {
+            Self::#ident { #(#fields,)* .. } => #num_bytes #(+ #trailing)*,
+        }
+    }
+
+    pub(crate) fn max_len(&self, word: &Word) -> TokenStream2 {
+        //! Returns a constant expression for the largest number of bytes this instruction can
+        //! occupy or `None` if its length is not bounded (e.g. it has a payload).
+        let num_bytes = self.opcode().length(word);
+        let trailing = self.trailing().iter().map(|field| field.max_len());
+        quote! {
+            {
+                let len: ::std::option::Option<::std::primitive::usize> = Some(#num_bytes);
+                #(let len = match (len, #trailing) {
+                    (Some(len), Some(field_len)) => Some(len + field_len),
+                    _ => None,
+                };)*
+                len
+            }
         }
     }
 
-    pub(crate) fn decoder_block(&self) -> TokenStream2 {
+    pub(crate) fn cycles_arm(&self) -> Option<TokenStream2> {
         match self {
-            Instruction::WithVars(instr) => instr.decoder_block(),
-            Instruction::Unit(instr) => instr.decoder_block(),
+            Instruction::WithVars(instr) => instr.cycles_arm(),
+            Instruction::Unit(instr) => instr.cycles_arm(),
         }
     }
+
     pub(crate) fn opcode(&self) -> &Opcode {
         match self {
             Instruction::WithVars(instr) => &instr.opcode,
             Instruction::Unit(instr) => &instr.opcode,
         }
     }
+
+    pub(crate) fn ident(&self) -> &Ident {
+        match self {
+            Instruction::WithVars(instr) => &instr.ident,
+            Instruction::Unit(instr) => &instr.ident,
+        }
+    }
+
+    pub(crate) fn attrs(&self) -> &InstrAttrs {
+        match self {
+            Instruction::WithVars(instr) => &instr.attrs,
+            Instruction::Unit(instr) => &instr.attrs,
+        }
+    }
+
+    pub(crate) fn attrs_mut(&mut self) -> &mut InstrAttrs {
+        match self {
+            Instruction::WithVars(instr) => &mut instr.attrs,
+            Instruction::Unit(instr) => &mut instr.attrs,
+        }
+    }
+
+    pub(crate) fn weight(&self) -> u64 {
+        //! Instructions without a weight are assumed to be executed once.
+        self.attrs().weight.unwrap_or(1)
+    }
+
+    pub(crate) fn trailing(&self) -> &[TrailingField] {
+        match self {
+            Instruction::WithVars(instr) => &instr.trailing,
+            Instruction::Unit(_) => &[],
+        }
+    }
 }
 
 impl Parse for Instruction {
@@ -121,70 +225,632 @@ impl Parse for Instruction {
         let attr: Vec<Attribute> = input.call(Attribute::parse_outer)?;
         let _: Visibility = input.parse()?;
         let ident: Ident = input.parse()?;
-        let opcode = Opcode::from_attrs(&ident, attr)?;
+        let opcode = Opcode::from_attrs(&ident, &attr)?;
+        let attrs = InstrAttrs::from_attrs(&attr)?;
         if input.peek(Brace) {
             let fields = input.parse()?;
-            let instr = InstrWithVars::new(ident, fields, opcode)?;
+            let instr = InstrWithVars::new(ident, fields, opcode, attrs)?;
             Ok(Instruction::WithVars(instr))
         } else if input.peek(Paren) {
             let fields: FieldsUnnamed = input.parse()?;
             Err(Error::new(fields.span(), "Variants with unnamed fields not supported. Use Enum::Variant{X:usize, Y:u32} notation"))
         } else {
-            Ok(Instruction::Unit(UnitInstr { ident, opcode }))
+            Ok(Instruction::Unit(UnitInstr {
+                ident,
+                opcode,
+                attrs,
+            }))
         }
     }
 }
 
+/// This struct collects the optional attributes of an instruction besides its opcode.
+/// * `cycles` is the expression given by `#[cycles = 4]` or `#[cycles = "4 + page_cross"]`. In
+///   the string form the expression can use the fields of the instruction.
+/// * `when` is the condition given by `#[when = "ctx.thumb"]`. The instruction is only decoded by
+///   `decode_with(mem, ctx)` when the condition holds for the decode context `ctx`.
+/// * `extension` is the name given by `#[extension = "M"]`. The instruction is only decoded by
+///   `decode_for(mem, enabled)` if this extension is enabled.
+/// * `weight` is the execution frequency given by `#[weight = 100]` or read from the profile of
+///   the instruction set. The decoder is built to take the fewest branches for the heaviest
+///   instructions.
+pub(crate) struct InstrAttrs {
+    pub(crate) cycles: Option<Expr>,
+    pub(crate) when: Option<Expr>,
+    pub(crate) extension: Option<LitStr>,
+    pub(crate) weight: Option<u64>,
+}
+
+impl InstrAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> SynResult<InstrAttrs> {
+        let mut cycles = None;
+        let mut when = None;
+        let mut extension = None;
+        let mut weight = None;
+        for attribute in attrs {
+            if attribute.path.is_ident("cycles") {
+                cycles = Some(Self::parse_cycles(attribute)?);
+            } else if attribute.path.is_ident("when") {
+                when = Some(Self::parse_when(attribute)?);
+            } else if attribute.path.is_ident("extension") {
+                extension = Some(Self::parse_extension(attribute)?);
+            } else if attribute.path.is_ident("weight") {
+                weight = Some(Self::parse_weight(attribute)?);
+            }
+        }
+        Ok(InstrAttrs {
+            cycles,
+            when,
+            extension,
+            weight,
+        })
+    }
+
+    fn parse_weight(attribute: &Attribute) -> SynResult<u64> {
+        //! Parses the execution frequency in `#[weight = 100]`.
+        let meta = attribute.parse_meta()?;
+        match meta {
+            Meta::NameValue(name_value) => match name_value.lit {
+                Lit::Int(int_lit) => int_lit.base10_parse(),
+                lit => Err(Error::new(
+                    lit.span(),
+                    "Weights must be defined as integer literals (e.g. #[weight = 100])",
+                )),
+            },
+            _ => Err(Error::new(
+                meta.span(),
+                "Weight attribute declared but no value given (e.g. #[weight = 100])",
+            )),
+        }
+    }
+
+    fn parse_extension(attribute: &Attribute) -> SynResult<LitStr> {
+        //! Parses the extension name in `#[extension = "M"]`. The name is used for the generated
+        //! `EXT_M` constant, so it has to be a valid identifier.
+        let meta = attribute.parse_meta()?;
+        match meta {
+            Meta::NameValue(name_value) => match name_value.lit {
+                Lit::Str(str_lit) => match syn::parse_str::<Ident>(&str_lit.value()) {
+                    Ok(_) => Ok(str_lit),
+                    Err(_) => Err(Error::new(
+                        str_lit.span(),
+                        "Extension names must be valid identifiers (e.g. #[extension = \"M\"])",
+                    )),
+                },
+                lit => Err(Error::new(
+                    lit.span(),
+                    "Extension names must be defined as string literals (e.g. #[extension = \"M\"])",
+                )),
+            },
+            _ => Err(Error::new(
+                meta.span(),
+                "Extension attribute declared but no name given (e.g. #[extension = \"M\"])",
+            )),
+        }
+    }
+
+    fn parse_when(attribute: &Attribute) -> SynResult<Expr> {
+        //! Parses the condition in `#[when = "ctx.thumb"]`.
+        let meta = attribute.parse_meta()?;
+        match meta {
+            Meta::NameValue(name_value) => match name_value.lit {
+                Lit::Str(str_lit) => str_lit.parse(),
+                lit => Err(Error::new(
+                    lit.span(),
+                    "Decode conditions must be defined as string literals (e.g. #[when = \"ctx.thumb\"])",
+                )),
+            },
+            _ => Err(Error::new(
+                meta.span(),
+                "When attribute declared but no condition given (e.g. #[when = \"ctx.thumb\"])",
+            )),
+        }
+    }
+
+    fn parse_cycles(attribute: &Attribute) -> SynResult<Expr> {
+        //! Parses `#[cycles = 4]` into a literal expression and `#[cycles = "4 + page_cross"]`
+        //! into the expression contained in the string.
+        let meta = attribute.parse_meta()?;
+        match meta {
+            Meta::NameValue(name_value) => match name_value.lit {
+                Lit::Int(int_lit) => Ok(parse_quote!(#int_lit)),
+                Lit::Str(str_lit) => str_lit.parse(),
+                lit => Err(Error::new(
+                    lit.span(),
+                    "Cycle counts must be defined as integer or string literals (e.g. #[cycles = 4] or #[cycles = \"4 + page_cross\"])",
+                )),
+            },
+            _ => Err(Error::new(
+                meta.span(),
+                "Cycles attribute declared but no value given (e.g. #[cycles = 4])",
+            )),
+        }
+    }
+}
+
+fn mentions_ident(tokens: TokenStream2, ident: &Ident) -> bool {
+    //! Returns true if `ident` appears anywhere in `tokens`. This is used to only bind the fields
+    //! of an instruction that are actually used in a user supplied expression.
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(i) => i == *ident,
+        TokenTree::Group(g) => mentions_ident(g.stream(), ident),
+        _ => false,
+    })
+}
+
 pub(crate) struct UnitInstr {
     ident: Ident,
     opcode: Opcode,
+    attrs: InstrAttrs,
 }
 
 impl UnitInstr {
-    fn decoder_block(&self) -> TokenStream2 {
+    fn decoder_block(&self, word: &Word) -> TokenStream2 {
         let self_ident = &self.ident;
-        let num_bytes = self.opcode.num_bytes();
-        let conditions = self.opcode.build_match_conditions();
+        let num_bytes = self.opcode.length(word);
+        let conditions = self.opcode.build_match_conditions(word);
 
         quote! {
             if #conditions { Ok((#num_bytes, Self::#self_ident)) }
         }
     }
 
-    fn encoder_block(&self) -> TokenStream2 {
+    fn cycles_arm(&self) -> Option<TokenStream2> {
         let self_ident = &self.ident;
-        let num_bytes = self.opcode.num_bytes();
+        self.attrs.cycles.as_ref().map(|cycles| {
+            quote! {
+                Self::#self_ident => (#cycles) as ::std::primitive::u32,
+            }
+        })
+    }
+
+    fn encoder_block(&self, word: &Word) -> TokenStream2 {
+        let self_ident = &self.ident;
+        let num_words = self.opcode.num_words(word);
+        let num_bytes = self.opcode.length(word);
         let code_strings: Vec<LitInt> = self
             .opcode
-            .code_strings()
+            .code_strings(word)
             .map(|s| LitInt::new(&format!("0b{}", s), self.opcode.span()))
             .collect();
         let byte_indices = 0..code_strings.len();
+        let store = self.opcode.build_store(word);
 
         quote! {
             Self::#self_ident => {
-                if buf.len() < #num_bytes {
+                if buf.len() < #num_words {
                     return Err(imperative_rs::EncodeError::UnexpectedEOF);
                 }
                 #(buf[#byte_indices] = #code_strings);*;
+                #store
                 return Ok(#num_bytes);
             },
         }
     }
 }
 
+/// Fields that are not encoded in the opcode itself but in the bytes following it. They are
+/// decoded in order of declaration after the opcode and each of them adds to the length of the
+/// instruction.
+/// * `Operand` is a field marked with `#[operand]` whose type implements `InstructionSet` itself
+///   (e.g. an x86 ModRM byte or a 68000 effective address).
+/// * `Payload` is a `Vec<u8>` marked with `#[len = ".."]`. The expression gives the number of
+///   bytes in the payload and can use the other fields of the instruction (e.g. EVM `PUSH1..32`).
+/// * `Varint` is an integer marked with `#[varint = ".."]` that is encoded with a variable number
+///   of bytes (e.g. WebAssembly immediates).
+pub(crate) enum TrailingField {
+    Operand {
+        ident: Ident,
+        ty: Type,
+    },
+    Payload {
+        ident: Ident,
+        len: Expr,
+    },
+    Varint {
+        ident: Ident,
+        ty: Type,
+        kind: VarintKind,
+    },
+}
+
+/// The encodings supported by `#[varint = ".."]`. Their encoders and decoders are part of the
+/// `imperative_rs::varint` module.
+pub(crate) enum VarintKind {
+    Uleb128,
+    Sleb128,
+    Zigzag,
+}
+
+impl TrailingField {
+    fn from_field(field: &Field) -> SynResult<Option<TrailingField>> {
+        //! Returns the trailing field if the field is marked as one. Fields without marker are
+        //! variables encoded in the opcode.
+        let ident = field.ident.clone().unwrap();
+        for attr in &field.attrs {
+            if attr.path.is_ident("operand") {
+                return Ok(Some(TrailingField::Operand {
+                    ident,
+                    ty: field.ty.clone(),
+                }));
+            } else if attr.path.is_ident("len") {
+                if field.ty != parse_quote!(Vec<u8>) {
+                    return Err(Error::new(
+                        field.ty.span(),
+                        "Fields with a length (i.e. #[len = \"..\"]) need to be of type Vec<u8>",
+                    ));
+                }
+                let len = match attr.parse_meta()? {
+                    Meta::NameValue(name_value) => match name_value.lit {
+                        Lit::Str(str_lit) => str_lit.parse()?,
+                        lit => return Err(Error::new(
+                            lit.span(),
+                            "Lengths must be defined as string literals (e.g. #[len = \"n + 1\"])",
+                        )),
+                    },
+                    meta => return Err(Error::new(
+                        meta.span(),
+                        "Length attribute declared but no length given (e.g. #[len = \"n + 1\"])",
+                    )),
+                };
+                return Ok(Some(TrailingField::Payload { ident, len }));
+            } else if attr.path.is_ident("varint") {
+                let kind = match attr.parse_meta()? {
+                    Meta::NameValue(MetaNameValue {
+                        lit: Lit::Str(str_lit),
+                        ..
+                    }) => match str_lit.value().as_str() {
+                        "uleb128" => VarintKind::Uleb128,
+                        "sleb128" => VarintKind::Sleb128,
+                        "zigzag" => VarintKind::Zigzag,
+                        _ => return Err(Error::new(
+                            str_lit.span(),
+                            "Unknown varint encoding. Supported encodings are: uleb128, sleb128, zigzag",
+                        )),
+                    },
+                    meta => return Err(Error::new(
+                        meta.span(),
+                        "Varints must name their encoding (e.g. #[varint = \"uleb128\"])",
+                    )),
+                };
+                let signed = match kind {
+                    VarintKind::Uleb128 => false,
+                    VarintKind::Sleb128 | VarintKind::Zigzag => true,
+                };
+                let supported: &[Type] = if signed {
+                    &[
+                        parse_quote!(i8),
+                        parse_quote!(i16),
+                        parse_quote!(i32),
+                        parse_quote!(i64),
+                        parse_quote!(i128),
+                        parse_quote!(isize),
+                    ]
+                } else {
+                    &[
+                        parse_quote!(u8),
+                        parse_quote!(u16),
+                        parse_quote!(u32),
+                        parse_quote!(u64),
+                        parse_quote!(u128),
+                        parse_quote!(usize),
+                    ]
+                };
+                if !supported.contains(&field.ty) {
+                    let msg = if signed {
+                        "Signed varints (i.e. sleb128 and zigzag) need to be a signed integer"
+                    } else {
+                        "Unsigned varints (i.e. uleb128) need to be an unsigned integer"
+                    };
+                    return Err(Error::new(field.ty.span(), msg));
+                }
+                return Ok(Some(TrailingField::Varint {
+                    ident,
+                    ty: field.ty.clone(),
+                    kind,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn ident(&self) -> &Ident {
+        match self {
+            TrailingField::Operand { ident, .. }
+            | TrailingField::Payload { ident, .. }
+            | TrailingField::Varint { ident, .. } => ident,
+        }
+    }
+
+    fn min_len(&self) -> TokenStream2 {
+        //! Returns a constant expression for the least number of bytes of this field.
+        match self {
+            TrailingField::Operand { ty, .. } => {
+                quote! { <#ty as imperative_rs::InstructionSet>::MIN_LEN }
+            }
+            TrailingField::Payload { .. } => quote! { 0 },
+            TrailingField::Varint { .. } => quote! { 1 },
+        }
+    }
+
+    fn max_len(&self) -> TokenStream2 {
+        //! Returns a constant expression for the largest number of bytes of this field or `None`
+        //! if it is not bounded. A varint has 7 bits per byte.
+        match self {
+            TrailingField::Operand { ty, .. } => {
+                quote! { <#ty as imperative_rs::InstructionSet>::MAX_LEN }
+            }
+            TrailingField::Payload { .. } => {
+                quote! { ::std::option::Option::<::std::primitive::usize>::None }
+            }
+            TrailingField::Varint { ty, .. } => {
+                let max_bytes = size_of(ty).div_ceil(7);
+                quote! { Some(#max_bytes) }
+            }
+        }
+    }
+
+    fn encoded_len(&self) -> TokenStream2 {
+        //! Returns an expression for the number of bytes this field is encoded into. The field is
+        //! bound by reference.
+        let ident = self.ident();
+        match self {
+            TrailingField::Operand { ty, .. } => {
+                quote! { <#ty as imperative_rs::InstructionSet>::encoded_len(#ident) }
+            }
+            TrailingField::Payload { .. } => quote! { #ident.len() },
+            TrailingField::Varint { kind, .. } => {
+                let (_, _, wide) = TrailingField::varint_fns(kind);
+                let len = match kind {
+                    VarintKind::Uleb128 => quote!(uleb128_len),
+                    VarintKind::Sleb128 => quote!(sleb128_len),
+                    VarintKind::Zigzag => quote!(zigzag_len),
+                };
+                quote! { imperative_rs::varint::#len(*#ident as ::std::primitive::#wide) }
+            }
+        }
+    }
+
+    fn length_decoder(&self) -> TokenStream2 {
+        //! Same as `decoder(..)` for `instruction_len(..)`. Only adds the length of the field to
+        //! `num_bytes`. Varints are still decoded since lengths of payloads can depend on them.
+        match self {
+            TrailingField::Operand { ty, .. } => quote! {
+                num_bytes += <#ty as imperative_rs::InstructionSet>::instruction_len(&mem[num_bytes..])?;
+            },
+            TrailingField::Payload { len, .. } => quote! {
+                let payload_bytes = (#len) as ::std::primitive::usize;
+                //the length is read from memory, so it can be anything up to `usize::MAX`
+                if mem.len() - num_bytes < payload_bytes {
+                    return Err(imperative_rs::DecodeError::UnexpectedEOF);
+                }
+                num_bytes += payload_bytes;
+            },
+            TrailingField::Varint { .. } => self.decoder(&Word::byte()),
+        }
+    }
+
+    fn is_copy(&self) -> bool {
+        //! Returns true if the field can be used by value in expressions of other attributes.
+        matches!(self, TrailingField::Varint { .. })
+    }
+
+    pub(crate) fn is_byte_oriented(&self) -> bool {
+        //! Returns true if the field can only be decoded from bytes. Operands are decoded with
+        //! the memory of the instruction set they are part of.
+        !matches!(self, TrailingField::Operand { .. })
+    }
+
+    fn decoder(&self, word: &Word) -> TokenStream2 {
+        //! Decodes the field from the memory following the bytes decoded so far into a local
+        //! variable of the same name and adds its length to `num_bytes`. The fields decoded
+        //! before are available as local variables.
+        let ident = self.ident();
+        if word.bitstream {
+            return self.bit_decoder();
+        }
+        match self {
+            TrailingField::Operand { ty, .. } if word.is_byte() => quote! {
+                let (operand_bytes, #ident) = <#ty as imperative_rs::InstructionSet>::decode(&mem[num_bytes..])?;
+                num_bytes += operand_bytes;
+            },
+            TrailingField::Operand { ty, .. } => {
+                let word_ty = &word.ty;
+                quote! {
+                    let (operand_bytes, #ident) = <#ty as imperative_rs::WordInstructionSet<#word_ty>>::decode_words(&mem[num_bytes..])?;
+                    num_bytes += operand_bytes;
+                }
+            }
+            TrailingField::Payload { len, .. } => quote! {
+                let payload_bytes = (#len) as ::std::primitive::usize;
+                //the length is read from memory, so it can be anything up to `usize::MAX`
+                if mem.len() - num_bytes < payload_bytes {
+                    return Err(imperative_rs::DecodeError::UnexpectedEOF);
+                }
+                let #ident = mem[num_bytes..num_bytes + payload_bytes].to_vec();
+                num_bytes += payload_bytes;
+            },
+            TrailingField::Varint { ty, kind, .. } => {
+                let (decode, _, _) = TrailingField::varint_fns(kind);
+                quote! {
+                    let (varint_bytes, value) = imperative_rs::varint::#decode(&mem[num_bytes..])?;
+                    let #ident: #ty = ::std::convert::TryFrom::try_from(value)
+                        .map_err(|_| imperative_rs::DecodeError::Overflow)?;
+                    num_bytes += varint_bytes;
+                }
+            }
+        }
+    }
+
+    fn encoder(&self, copy_idents: &[&Ident], word: &Word) -> TokenStream2 {
+        //! Encodes the field into the buffer following the bytes encoded so far and adds its
+        //! length to `num_bytes`. The fields of the instruction are bound by reference, the ones
+        //! in `copy_idents` can be dereferenced when they are needed by value.
+        let ident = self.ident();
+        if word.bitstream {
+            return self.bit_encoder(copy_idents);
+        }
+        match self {
+            TrailingField::Operand { .. } if word.is_byte() => quote! {
+                num_bytes += imperative_rs::InstructionSet::encode(#ident, &mut buf[num_bytes..])?;
+            },
+            TrailingField::Operand { .. } => {
+                let word_ty = &word.ty;
+                quote! {
+                    num_bytes += imperative_rs::WordInstructionSet::<#word_ty>::encode_words(#ident, &mut buf[num_bytes..])?;
+                }
+            }
+            TrailingField::Payload { len, .. } => {
+                let used = copy_idents
+                    .iter()
+                    .filter(|copy_ident| mentions_ident(len.to_token_stream(), copy_ident));
+                let used_again = used.clone();
+                quote! {
+                    let payload_bytes = {
+                        #(let #used = *#used_again;)*
+                        (#len) as ::std::primitive::usize
+                    };
+                    if #ident.len() != payload_bytes {
+                        return Err(imperative_rs::EncodeError::InvalidLength);
+                    }
+                    if buf.len() - num_bytes < payload_bytes {
+                        return Err(imperative_rs::EncodeError::UnexpectedEOF);
+                    }
+                    buf[num_bytes..num_bytes + payload_bytes].copy_from_slice(#ident);
+                    num_bytes += payload_bytes;
+                }
+            }
+            TrailingField::Varint { kind, .. } => {
+                let (_, encode, wide) = TrailingField::varint_fns(kind);
+                quote! {
+                    num_bytes += imperative_rs::varint::#encode(
+                        *#ident as ::std::primitive::#wide,
+                        &mut buf[num_bytes..],
+                    )?;
+                }
+            }
+        }
+    }
+    fn varint_fns(kind: &VarintKind) -> (TokenStream2, TokenStream2, TokenStream2) {
+        //! Returns the decoder, the encoder and the type the encoder takes for a varint.
+        match kind {
+            VarintKind::Uleb128 => (quote!(decode_uleb128), quote!(encode_uleb128), quote!(u128)),
+            VarintKind::Sleb128 => (quote!(decode_sleb128), quote!(encode_sleb128), quote!(i128)),
+            VarintKind::Zigzag => (quote!(decode_zigzag), quote!(encode_zigzag), quote!(i128)),
+        }
+    }
+
+    fn bit_decoder(&self) -> TokenStream2 {
+        //! Same as `decoder(..)` for a bit stream. The field starts at bit `bit_offset +
+        //! num_bytes` of `bits_mem` and `num_bytes` counts bits.
+        let ident = self.ident();
+        match self {
+            TrailingField::Operand { ty, .. } => quote! {
+                let (operand_bits, #ident) = <#ty>::decode_bits(bits_mem, bit_offset + num_bytes)?;
+                num_bytes += operand_bits;
+            },
+            TrailingField::Payload { len, .. } => quote! {
+                let payload_bytes = (#len) as ::std::primitive::usize;
+                if (bits_mem.len() * 8).saturating_sub(bit_offset + num_bytes) / 8 < payload_bytes {
+                    return Err(imperative_rs::DecodeError::UnexpectedEOF);
+                }
+                let mut #ident = vec![0u8; payload_bytes];
+                imperative_rs::bits::read(bits_mem, bit_offset + num_bytes, &mut #ident);
+                num_bytes += 8 * payload_bytes;
+            },
+            TrailingField::Varint { ty, kind, .. } => {
+                let (decode, _, _) = TrailingField::varint_fns(kind);
+                quote! {
+                    let mut varint_window = [0u8; 19];
+                    let varint_bits = imperative_rs::bits::read(bits_mem, bit_offset + num_bytes, &mut varint_window);
+                    let (varint_bytes, value) = imperative_rs::varint::#decode(&varint_window[..varint_bits / 8])?;
+                    let #ident: #ty = ::std::convert::TryFrom::try_from(value)
+                        .map_err(|_| imperative_rs::DecodeError::Overflow)?;
+                    num_bytes += 8 * varint_bytes;
+                }
+            }
+        }
+    }
+
+    fn bit_encoder(&self, copy_idents: &[&Ident]) -> TokenStream2 {
+        //! Same as `encoder(..)` for a bit stream. The field is written to bit `bit_offset +
+        //! num_bytes` of `bits_buf` and `num_bytes` counts bits.
+        let ident = self.ident();
+        match self {
+            TrailingField::Operand { .. } => quote! {
+                num_bytes += #ident.encode_bits(bits_buf, bit_offset + num_bytes)?;
+            },
+            TrailingField::Payload { len, .. } => {
+                let used = copy_idents
+                    .iter()
+                    .filter(|copy_ident| mentions_ident(len.to_token_stream(), copy_ident));
+                let used_again = used.clone();
+                quote! {
+                    let payload_bytes = {
+                        #(let #used = *#used_again;)*
+                        (#len) as ::std::primitive::usize
+                    };
+                    if #ident.len() != payload_bytes {
+                        return Err(imperative_rs::EncodeError::InvalidLength);
+                    }
+                    imperative_rs::bits::write(bits_buf, bit_offset + num_bytes, #ident, 8 * payload_bytes)?;
+                    num_bytes += 8 * payload_bytes;
+                }
+            }
+            TrailingField::Varint { kind, .. } => {
+                let (_, encode, wide) = TrailingField::varint_fns(kind);
+                quote! {
+                    let mut varint_window = [0u8; 19];
+                    let varint_bytes = imperative_rs::varint::#encode(
+                        *#ident as ::std::primitive::#wide,
+                        &mut varint_window,
+                    )?;
+                    imperative_rs::bits::write(bits_buf, bit_offset + num_bytes, &varint_window[..varint_bytes], 8 * varint_bytes)?;
+                    num_bytes += 8 * varint_bytes;
+                }
+            }
+        }
+    }
+}
+
+/// The byte order of a variable that spans multiple bytes of the opcode. `Little` is set by
+/// `#[endian = "little"]` (e.g. the 16 bit immediates of the 8080 family).
+#[derive(PartialEq)]
+pub(crate) enum Endian {
+    Big,
+    Little,
+}
+
 pub(crate) struct InstrWithVars {
     ident: Ident,
     opcode: Opcode,
-    var_map: HashMap<char, (Ident, Type)>,
+    var_map: HashMap<char, (Ident, Type, Endian)>,
+    slot_map: HashMap<char, (Ident, Type)>,
+    trailing: Vec<TrailingField>,
+    attrs: InstrAttrs,
 }
 
 impl InstrWithVars {
-    fn new(ident: Ident, fields: FieldsNamed, opcode: Opcode) -> SynResult<Self> {
+    fn new(
+        ident: Ident,
+        fields: FieldsNamed,
+        opcode: Opcode,
+        attrs: InstrAttrs,
+    ) -> SynResult<Self> {
+        let mut trailing = vec![];
+        for field in fields.named.iter() {
+            if let Some(field) = TrailingField::from_field(field)? {
+                trailing.push(field);
+            }
+        }
+        let (var_map, slot_map) = Self::map_variables(fields)?;
         let instr = Self {
             ident,
             opcode,
-            var_map: Self::map_variables(fields)?,
+            var_map,
+            slot_map,
+            trailing,
+            attrs,
         };
         instr.check_opcode()?;
         Ok(instr)
@@ -195,16 +861,17 @@ impl InstrWithVars {
         let mut variables: HashMap<&char, (&Ident, &Type, usize)> = self
             .var_map
             .iter()
-            .map(|(c, (i, t))| (c, (i, t, 0)))
+            .map(|(c, (i, t, _))| (c, (i, t, 0)))
             .collect();
 
-        for bit in self.opcode.bytes.iter().flatten() {
+        for bit in self.opcode.bits.iter() {
             match variables.get_mut(bit) {
                 Some(entry) => entry.2 += 1,
                 None => {
-                    if *bit != '0' && *bit != '1' && *bit != '*' {
+                    if *bit != '0' && *bit != '1' && *bit != '*' && !self.slot_map.contains_key(bit)
+                    {
                         let err = Error::new(self.opcode.span,
-                                &format!("Opcode contains {} which is neither a valid digit nor a variable name.", bit));
+                                format!("Opcode contains {} which is neither a valid digit nor a variable name.", bit));
                         if let Err(ref mut total_error) = res {
                             total_error.combine(err);
                         } else {
@@ -219,7 +886,7 @@ impl InstrWithVars {
             if *num_bits == 0 {
                 let err = Error::new(
                     ident.span(),
-                    &format!(
+                    format!(
                         "Variable {:?} (with symbol: {:?}) declared but never used in opcode.",
                         ident, c
                     ),
@@ -233,7 +900,7 @@ impl InstrWithVars {
             if size_of(ty) < *num_bits {
                 let err = Error::new(
                     ident.span(),
-                    &format!(
+                    format!(
                         "Variable {} (with symbol: {}) has fewer bits ({}) than positions in opcode ({}). (e.g. u8 with 9 bits in opcode)",
                         ident, c, size_of(ty), num_bits
                     ),
@@ -245,10 +912,50 @@ impl InstrWithVars {
                 }
             }
         }
+        for (c, (ident, _)) in self.slot_map.iter() {
+            if !self.opcode.bits.contains(c) {
+                let err = Error::new(
+                    ident.span(),
+                    format!(
+                        "Slot {:?} (with symbol: {:?}) declared but never used in opcode.",
+                        ident, c
+                    ),
+                );
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+        }
+        //little endian variables are assembled from whole bytes
+        for (c, (ident, _, endian)) in self.var_map.iter() {
+            let num_bits = self.opcode.bits.iter().filter(|&bit| bit == c).count();
+            if *endian == Endian::Little && num_bits % 8 != 0 {
+                let err = Error::new(
+                    ident.span(),
+                    format!(
+                        "Little endian variable {} (with symbol: {}) needs a multiple of 8 positions in opcode (found {}).",
+                        ident, c, num_bits
+                    ),
+                );
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+        }
         res
     }
 
-    fn map_variables(fields: FieldsNamed) -> SynResult<HashMap<char, (Ident, Type)>> {
+    #[allow(clippy::type_complexity)]
+    fn map_variables(
+        fields: FieldsNamed,
+    ) -> SynResult<(
+        HashMap<char, (Ident, Type, Endian)>,
+        HashMap<char, (Ident, Type)>,
+    )> {
         //! This functions takes an iterator over the named fields of an enum variant (i.e.
         //! Enum::Variant{*var0:type0, var1:type1,...*}) which represent the variables of the
         //! instruction. It tries to construct a hashmap with the
@@ -261,12 +968,36 @@ impl InstrWithVars {
         //! * if the variable is actually used in the opcode
         //! * if the variable name is valid (i.e. length 1, not hexdigit)
         //! * if the variable is of a supported type
+        //! * if a valid `endian` attribute is given
+        //!
         //! If a check fails for a field, the other checks are omitted. If any check fails for a
-        //! given field the rest of the fields will still be checked.
+        //! given field the rest of the fields will still be checked. Trailing fields (e.g.
+        //! `#[operand]`) are not encoded in the opcode and are skipped. Slots (i.e. `#[slot]`)
+        //! are named like variables but are instructions themselves. They are returned in a
+        //! separate map.
         let mut res: SynResult<()> = Ok(());
         let mut variables = HashMap::new();
+        let mut slots = HashMap::new();
         for f in fields.named.into_iter() {
             let ident = f.ident.as_ref().unwrap();
+            if TrailingField::from_field(&f)?.is_some() {
+                if let Some(attr) = f.attrs.iter().find(|&attr| {
+                    attr.path.is_ident("variable")
+                        || attr.path.is_ident("endian")
+                        || attr.path.is_ident("slot")
+                }) {
+                    let err = Error::new(
+                        attr.span(),
+                        "Fields that follow the opcode can't be variables in the opcode",
+                    );
+                    if let Err(ref mut total_error) = res {
+                        total_error.combine(err);
+                    } else {
+                        res = Err(err);
+                    }
+                }
+                continue;
+            }
             let var_name = if let Some(attr) =
                 f.attrs.iter().find(|&attr| attr.path.is_ident("variable"))
             {
@@ -329,6 +1060,34 @@ impl InstrWithVars {
                 }
                 continue;
             }
+            if let Some(attr) = f.attrs.iter().find(|&attr| attr.path.is_ident("slot")) {
+                if let Some(endian) = f.attrs.iter().find(|&attr| attr.path.is_ident("endian")) {
+                    let err = Error::new(
+                        endian.span(),
+                        "Slots are decoded as instructions and have no byte order",
+                    );
+                    if let Err(ref mut total_error) = res {
+                        total_error.combine(err);
+                    } else {
+                        res = Err(err);
+                    }
+                    continue;
+                }
+                if !attr.tokens.is_empty() {
+                    let err = Error::new(
+                        attr.span(),
+                        "Slots are marked with #[slot] and take no arguments",
+                    );
+                    if let Err(ref mut total_error) = res {
+                        total_error.combine(err);
+                    } else {
+                        res = Err(err);
+                    }
+                    continue;
+                }
+                slots.insert(var_name, (f.ident.unwrap(), f.ty));
+                continue;
+            }
             if !is_supported_type(&f.ty) {
                 let err = Error::new(
                     ident.span(),
@@ -341,41 +1100,211 @@ impl InstrWithVars {
                 }
                 continue;
             }
+            let endian = if let Some(attr) =
+                f.attrs.iter().find(|&attr| attr.path.is_ident("endian"))
+            {
+                match attr.parse_meta()? {
+                    Meta::NameValue(MetaNameValue {
+                        lit: Lit::Str(str_lit),
+                        ..
+                    }) if str_lit.value() == "little" => Endian::Little,
+                    Meta::NameValue(MetaNameValue {
+                        lit: Lit::Str(str_lit),
+                        ..
+                    }) if str_lit.value() == "big" => Endian::Big,
+                    meta => {
+                        let err = Error::new(
+                            meta.span(),
+                            "Endianness must be either \"little\" or \"big\" (e.g. #[endian = \"little\"])",
+                        );
+                        if let Err(ref mut total_error) = res {
+                            total_error.combine(err);
+                        } else {
+                            res = Err(err);
+                        }
+                        continue;
+                    }
+                }
+            } else {
+                Endian::Big
+            };
             let (ident, ty) = (f.ident, f.ty);
-            variables.insert(var_name, (ident.unwrap(), ty));
+            variables.insert(var_name, (ident.unwrap(), ty, endian));
         }
-        res.map(|_| variables)
+        res.map(|_| (variables, slots))
     }
 
-    fn decoder_block(&self) -> TokenStream2 {
+    fn decoder_block(&self, word: &Word) -> TokenStream2 {
         //! This constructs the last if-clause and decoder when decoding a byte buffer. This code
         //! is the leaf of the binary tree constructed by the matcher. But the matcher only rules
         //! out more and more instruction until one is left. This doesn't mean that this last
         //! instruction is actually correct. This function requests this match condition, the
         //! length of the instruction in bytes and the decoder for the instructions variables from
-        //! the `Opcode` and puts it all together into a complete decoder for this instruction
-        let num_bytes = self.opcode.num_bytes();
-        let var_decoders = self.opcode.build_var_decoders(&self.var_map);
-        let match_conditions = self.opcode.build_match_conditions();
+        //! the `Opcode` and puts it all together into a complete decoder for this instruction.
+        //! Trailing fields are decoded after the opcode and add to the number of bytes.
+        let num_bytes = self.opcode.length(word);
+        let (var_idents, var_decoders): (Vec<Ident>, Vec<TokenStream2>) = self
+            .opcode
+            .build_var_decoders(&self.var_map, word)
+            .into_iter()
+            .unzip();
+        let load = if var_idents.is_empty() {
+            TokenStream2::new()
+        } else {
+            self.opcode.build_word_load(word)
+        };
+        let match_conditions = self.opcode.build_match_conditions(word);
         let ident = &self.ident;
+        if self.trailing.is_empty() && self.slot_map.is_empty() {
+            return quote! {
+                if #match_conditions {
+                    #load
+                    Ok((#num_bytes, Self::#ident{
+                        #(#var_idents: #var_decoders),*
+                    }))
+                }
+            };
+        }
+        let (slot_idents, slot_decoders): (Vec<Ident>, Vec<TokenStream2>) = self
+            .opcode
+            .build_slot_decoders(&self.slot_map, word)
+            .into_iter()
+            .unzip();
+        let trailing_decoders = self.trailing.iter().map(|field| field.decoder(word));
+        let trailing_idents = self.trailing.iter().map(|field| field.ident());
         quote! {
             if #match_conditions {
-                Ok((#num_bytes, Self::#ident{
-                    #var_decoders
+                #load
+                #(let #var_idents = #var_decoders;)*
+                #(let #slot_idents = #slot_decoders;)*
+                #[allow(unused_mut)]
+                let mut num_bytes = #num_bytes;
+                #(#trailing_decoders)*
+                Ok((num_bytes, Self::#ident{
+                    #(#var_idents,)*
+                    #(#slot_idents,)*
+                    #(#trailing_idents),*
                 }))
             }
         }
     }
 
-    fn encoder_block(&self) -> TokenStream2 {
+    fn length_block(&self, word: &Word) -> TokenStream2 {
+        //! Same as `decoder_block(..)` for `instruction_len(..)`. Variables are only decoded if
+        //! the length of a payload depends on them.
+        let num_bytes = self.opcode.length(word);
+        let match_conditions = self.opcode.build_match_conditions(word);
+        if self.trailing.is_empty() {
+            return quote! {
+                if #match_conditions { Ok(#num_bytes) }
+            };
+        }
+        let (var_idents, var_decoders): (Vec<Ident>, Vec<TokenStream2>) = self
+            .opcode
+            .build_var_decoders(&self.var_map, word)
+            .into_iter()
+            .filter(|(ident, _)| {
+                self.trailing.iter().any(|field| match field {
+                    TrailingField::Payload { len, .. } => {
+                        mentions_ident(len.to_token_stream(), ident)
+                    }
+                    _ => false,
+                })
+            })
+            .unzip();
+        let load = if var_idents.is_empty() {
+            TokenStream2::new()
+        } else {
+            self.opcode.build_word_load(word)
+        };
+        let trailing_decoders = self.trailing.iter().map(|field| field.length_decoder());
+        quote! {
+            if #match_conditions {
+                #load
+                #(let #var_idents = #var_decoders;)*
+                let mut num_bytes = #num_bytes;
+                #(#trailing_decoders)*
+                Ok(num_bytes)
+            }
+        }
+    }
+
+    fn cycles_arm(&self) -> Option<TokenStream2> {
+        //! This function constructs a match-arm for `cycles(&self)`. Only the fields that are
+        //! used in the cycles expression are bound. Variables and varints are bound by value and
+        //! other trailing fields by reference.
+        let ident = &self.ident;
+        self.attrs.cycles.as_ref().map(|cycles| {
+            let copy_trailing = self.trailing.iter().filter(|field| field.is_copy());
+            let used: Vec<&Ident> = self
+                .var_map
+                .values()
+                .map(|(ident, _, _)| ident)
+                .chain(copy_trailing.map(|field| field.ident()))
+                .filter(|ident| mentions_ident(cycles.to_token_stream(), ident))
+                .collect();
+            let used_trailing = self
+                .trailing
+                .iter()
+                .filter(|field| !field.is_copy())
+                .map(|field| field.ident())
+                .chain(self.slot_map.values().map(|(ident, _)| ident))
+                .filter(|ident| mentions_ident(cycles.to_token_stream(), ident));
+            quote! {
+                Self::#ident{ #(#used,)* #(#used_trailing,)* .. } => {
+                    #(let #used = *#used;)*
+                    (#cycles) as ::std::primitive::u32
+                },
+            }
+        })
+    }
+
+    fn encoder_block(&self, word: &Word) -> TokenStream2 {
         //! This function constructs a match-arm for the encoding of this variable. This is used in
         //! the match block of the encoder function
         let ident = &self.ident;
-        let encoder = self.opcode.build_encoder(&self.var_map);
-        let var_idents: Vec<&Ident> = self.var_map.iter().map(|(_, (ident, _))| ident).collect();
-
+        let mut encoder = self.opcode.build_encoder(&self.var_map, word);
+        encoder.extend(self.opcode.build_slot_encoders(&self.slot_map, word));
+        encoder.extend(self.opcode.build_store(word));
+        let var_idents: Vec<&Ident> = self
+            .var_map
+            .values()
+            .map(|(ident, _, _)| ident)
+            .chain(self.slot_map.values().map(|(ident, _)| ident))
+            .collect();
+        let trailing_idents: Vec<&Ident> =
+            self.trailing.iter().map(|field| field.ident()).collect();
+        let num_bytes = self.opcode.length(word);
+        if self.trailing.is_empty() {
+            return quote! {
+                Self::#ident{ #(#var_idents),* } => {
+                    #encoder
+                    return Ok(#num_bytes);
+                },
+            };
+        }
+        let copy_idents: Vec<&Ident> = self
+            .var_map
+            .values()
+            .map(|(ident, _, _)| ident)
+            .chain(
+                self.trailing
+                    .iter()
+                    .filter(|field| field.is_copy())
+                    .map(|field| field.ident()),
+            )
+            .collect();
+        let trailing_encoders = self
+            .trailing
+            .iter()
+            .map(|field| field.encoder(&copy_idents, word));
         quote! {
-            Self::#ident{ #(#var_idents),* } => {#encoder},
+            Self::#ident{ #(#var_idents,)* #(#trailing_idents),* } => {
+                #encoder
+                let mut num_bytes = #num_bytes;
+                #(#trailing_encoders)*
+                return Ok(num_bytes);
+            },
         }
     }
 }
@@ -397,46 +1326,146 @@ fn hex_to_bin_string(src_str: &str) -> String {
     res_str
 }
 
+/// The unit of memory the generated code reads and writes. `InstructionSet::decode(..)` works on
+/// bytes, `WordInstructionSet::decode_words(..)` on the word type given by
+/// `#[imperative(word = "u16")]`.
+/// * `bits` is the number of bits of each element of the memory that hold the opcode. With
+///   `#[imperative(word = "u16", word_bits = 12)]` only the lower 12 bits are used.
+/// * `ty` is the type of the elements of the memory.
+/// * `instr_word_bits` is set for bytes if the instruction set has a word width that is given
+///   by `word_bits`. The opcode is split into instruction words which are stored in whole bytes,
+///   with the unused upper bits being zero.
+/// * `bitstream` is set for `decode_bits(..)` and `encode_bits(..)`. The opcode is decoded from a
+///   window of bytes copied from the bit stream `bits_mem` at `bit_offset` and its length is
+///   counted in bits.
+#[derive(Clone)]
+pub(crate) struct Word {
+    pub(crate) bits: usize,
+    pub(crate) ty: TokenStream2,
+    pub(crate) instr_word_bits: Option<usize>,
+    pub(crate) bitstream: bool,
+}
+
+impl Word {
+    pub(crate) fn byte() -> Word {
+        Word {
+            bits: 8,
+            ty: quote!(::std::primitive::u8),
+            instr_word_bits: None,
+            bitstream: false,
+        }
+    }
+
+    pub(crate) fn bitstream() -> Word {
+        Word {
+            bitstream: true,
+            ..Word::byte()
+        }
+    }
+
+    pub(crate) fn is_byte(&self) -> bool {
+        self.ty.to_string() == Word::byte().ty.to_string()
+    }
+}
+
 /// This struct models the opcode given by the user. It offers multiple ways to iterate over
 /// the opcode:
 /// * `get_position_map_of(..)` returns an iterator over the bit and byte positions where the
-/// supplied variable symbol appears and enumerates the positions. "0b0x0x"-> (0, (0, 8)), (1,
-/// (0, 6))
+///   supplied variable symbol appears and enumerates the positions. "0b0x0x"-> (0, (0, 8)), (1,
+///   (0, 6))
 /// * `mask_strings(..)` returns an iterator over a strings. Each of which is a integer literal
-/// where all constant bits of the opcode are 1 and all variable bits are zero. `mem_byte & mask |
-/// code` is true for all bytes exactly when this opcode is hit.
+///   where all constant bits of the opcode are 1 and all variable bits are zero. `mem_byte & mask |
+///   code` is true for all bytes exactly when this opcode is hit.
 /// * `code_strings(..)` same as `mask_strings(..)` but returns codes: For each byte gives an int
-/// literal that is '1' when the opcode is constant and '1' in the corresponding bit position.
+///   literal that is '1' when the opcode is constant and '1' in the corresponding bit position.
 /// * `collision_iter(..)` returns an iterator over strings for each byte. Each string contains a
-/// '*' when the corresponding bit can be either '0' or '1' (i.e. containing a variable). If the
-/// opcode is constantly '0' or '1' in this bit the string contains '0' or '1' in this position.
+///   '*' when the corresponding bit can be either '0' or '1' (i.e. containing a variable). If the
+///   opcode is constantly '0' or '1' in this bit the string contains '0' or '1' in this position.
+///
 /// Additionally this struct produces encoder and decoder for the variables encoded in the opcode.
+/// All of them take the `Word` the memory consists of. Byte and bit positions are relative to
+/// these words. The opcode is stored as given and only padded to whole words when it is laid out
+/// in memory (see `Opcode::layout(..)`).
 pub(crate) struct Opcode {
-    bytes: Vec<[char; 8]>,
+    bits: Vec<char>,
     span: Span,
 }
 
 impl Opcode {
-    fn from_attrs(ident: &Ident, attrs: Vec<Attribute>) -> SynResult<Opcode> {
+    fn from_attrs(ident: &Ident, attrs: &[Attribute]) -> SynResult<Opcode> {
         //! Constructs an ´Opcode´ from an `Ident` and a `Vec<Attribute>`. Fails if no ´#[opcode =
         //! ".."]´ is defined.
         for attribute in attrs {
             if attribute.path.is_ident("opcode") {
-                let tokens: TokenStream = attribute.tokens.into();
+                let tokens: TokenStream = attribute.tokens.clone().into();
                 return syn::parse(tokens);
             }
         }
-        Err(Error::new(ident.span(), format!("No opcode defined for Instruction {}. Define Opcodes by adding #[opcode = \"0x...\"] above the Instruction", ident.to_string())))
+        Err(Error::new(ident.span(), format!("No opcode defined for Instruction {}. Define Opcodes by adding #[opcode = \"0x...\"] above the Instruction", ident)))
+    }
+
+    pub(crate) fn num_bits(&self) -> usize {
+        //! length of this opcode in bits as given by the user
+        self.bits.len()
+    }
+
+    pub(crate) fn num_words(&self, word: &Word) -> usize {
+        //! length of this opcode in words
+        self.layout(word).len() / word.bits
+    }
+
+    pub(crate) fn length(&self, word: &Word) -> usize {
+        //! length of this opcode as returned by the decoder. This is the number of words or
+        //! bits for a bit stream.
+        if word.bitstream {
+            self.num_bits()
+        } else {
+            self.num_words(word)
+        }
+    }
+
+    fn layout(&self, word: &Word) -> Vec<char> {
+        //! Returns the bits of this opcode as they are laid out in a memory of `word`s. Opcodes
+        //! are padded with '0' at the end to fill the last word. If the instruction set has a
+        //! word width (i.e. `word_bits`) each instruction word is padded with '0' at the front
+        //! to fill whole memory words instead. In a bit stream the bits following the opcode
+        //! belong to the next instruction, so the opcode is padded with '*'.
+        let mut layout = vec![];
+        if word.bitstream {
+            layout.extend(&self.bits);
+            layout.resize(self.bits.len().div_ceil(word.bits) * word.bits, '*');
+            return layout;
+        }
+        match word.instr_word_bits {
+            Some(instr_word_bits) => {
+                let padded_bits = instr_word_bits.div_ceil(word.bits) * word.bits;
+                for instr_word in self.bits.chunks(instr_word_bits) {
+                    layout.extend(std::iter::repeat_n('0', padded_bits - instr_word.len()));
+                    layout.extend(instr_word);
+                }
+            }
+            None => {
+                layout.extend(&self.bits);
+                let padded_bits = self.bits.len().div_ceil(word.bits) * word.bits;
+                layout.resize(padded_bits, '0');
+            }
+        }
+        layout
     }
 
-    fn num_bytes(&self) -> usize {
-        //! length of this opcode in bytes
-        self.bytes.len()
+    fn words(&self, word: &Word) -> Vec<Vec<char>> {
+        //! Splits the opcode into words.
+        self.layout(word)
+            .chunks(word.bits)
+            .map(|chunk| chunk.to_vec())
+            .collect()
     }
 
     fn get_position_map_of<'a>(
         &'a self,
         var_name: char,
+        endian: &Endian,
+        word: &Word,
     ) -> Box<
         dyn Iterator<
                 Item = (
@@ -447,32 +1476,47 @@ impl Opcode {
     > {
         //! For the given variable symbol returns the position where it occures (in (byte_idx,
         //! bit_idx) and how many bits it needs to be left shifted for it's target position.
+        //! Little endian variables are split into groups of 8 bits and the first group in the
+        //! opcode is the least significant.
+        let num_groups = self
+            .bits
+            .iter()
+            .filter(|&c| *c == var_name)
+            .count()
+            .div_ceil(8);
+        let little_endian = *endian == Endian::Little;
         Box::new(
-            self.bytes
-                .iter()
+            self.words(word)
+                .into_iter()
                 .enumerate()
                 .rev() //step through bytes in reverse order
                 .flat_map(move |(byte_idx, byte)| {
                     //iterate over byte in reverse
-                    byte.iter()
+                    byte.into_iter()
                         .enumerate()
                         .rev()
-                        .filter(move |(_, c)| **c == var_name) //filter positions that belong to this var
+                        .filter(move |(_, c)| *c == var_name) //filter positions that belong to this var
                         .map(move |(bit_idx, _)| (byte_idx, bit_idx))
                 }) //save bit and byte position
-                .enumerate() //count how many positions there are
-                .map(move |(idx, opcode_pos)| (idx, opcode_pos)),
-        ) //fill up bits in target starting at least significant bit
+                .enumerate() //fill up bits in target starting at least significant bit
+                .map(move |(tar_bit, pos)| {
+                    if little_endian {
+                        ((num_groups - 1 - tar_bit / 8) * 8 + tar_bit % 8, pos)
+                    } else {
+                        (tar_bit, pos)
+                    }
+                }),
+        )
     }
 
-    fn mask_strings<'a>(&'a self) -> Box<dyn Iterator<Item = String> + 'a> {
+    fn mask_strings(&self, word: &Word) -> Box<dyn Iterator<Item = String>> {
         //! Returns an iterator over the masks strings for each byte. Mask strings are used to
         //! identify this opcode: `mem[byte_idx] & mask[idx] == code[idx]` is true when and only
         //! when this opcode is hit
-        Box::new(self.bytes.iter().map(|byte| {
+        Box::new(self.words(word).into_iter().map(|byte| {
             let mut mask = "".to_string();
             for c in byte {
-                if *c == '0' || *c == '1' {
+                if c == '0' || c == '1' {
                     mask.push('1');
                 } else {
                     mask.push('0');
@@ -482,14 +1526,14 @@ impl Opcode {
         }))
     }
 
-    fn code_strings<'a>(&'a self) -> Box<dyn Iterator<Item = String> + 'a> {
+    fn code_strings(&self, word: &Word) -> Box<dyn Iterator<Item = String>> {
         //! Returns an iterator over the code strings for each byte. For further explainations see
         //! `Opcode::mask_strings(..)` above.
-        Box::new(self.bytes.iter().map(|byte| {
+        Box::new(self.words(word).into_iter().map(|byte| {
             let mut code = "".to_string();
             for c in byte {
-                if *c == '0' || *c == '1' {
-                    code.push(*c);
+                if c == '0' || c == '1' {
+                    code.push(c);
                 } else {
                     code.push('0');
                 }
@@ -498,42 +1542,141 @@ impl Opcode {
         }))
     }
 
-    pub(crate) fn collision_iter<'a>(&'a self) -> Box<dyn Iterator<Item = char> + 'a> {
+    pub(crate) fn collision_iter(&self, word: &Word) -> Box<dyn Iterator<Item = char>> {
         //! Returns an iterator over the bits of this opcode that is used by the `CollisionGuard`
         //! to check if this opcode can be distinguished from all other opcodes found so far.
         Box::new(
-            self.bytes
-                .iter()
-                .flatten()
-                .map(|&c| if c == '1' || c == '0' { c } else { '*' }),
+            self.layout(word)
+                .into_iter()
+                .map(|c| if c == '1' || c == '0' { c } else { '*' }),
         )
     }
 
-    fn build_var_decoders(&self, variables: &HashMap<char, (Ident, Type)>) -> TokenStream2 {
+    pub(crate) fn opcode_word(&self, word: &Word) -> Option<(TokenStream2, usize)> {
+        //! Returns an expression that loads the whole opcode from a memory of bytes into the
+        //! smallest unsigned integer that holds it and the number of bits of this integer. The
+        //! bytes are loaded big endian (i.e. as laid out in the opcode) and missing bytes are
+        //! zero. Opcodes of a single byte or of more than 16 bytes are matched byte by byte.
+        if !word.is_byte() || word.bitstream {
+            return None;
+        }
+        let num_bytes = self.num_words(word);
+        let int_bytes = match num_bytes {
+            2 => 2,
+            3..=4 => 4,
+            5..=8 => 8,
+            9..=16 => 16,
+            _ => return None,
+        };
+        let ty = Ident::new(&format!("u{}", 8 * int_bytes), Span::call_site());
+        let bytes = (0..int_bytes).map(|idx| {
+            if idx < num_bytes {
+                quote! { mem[#idx] }
+            } else {
+                quote! { 0 }
+            }
+        });
+        Some((
+            quote! { ::std::primitive::#ty::from_be_bytes([#(#bytes),*]) },
+            8 * int_bytes,
+        ))
+    }
+
+    pub(crate) fn build_word_load(&self, word: &Word) -> TokenStream2 {
+        //! Binds the opcode loaded by `opcode_word(..)` to `opcode_word` for the variable decoders.
+        match self.opcode_word(word) {
+            Some((load, _)) => quote! { let opcode_word = #load; },
+            None => TokenStream2::new(),
+        }
+    }
+
+    fn word_literal(&self, strings: Box<dyn Iterator<Item = String>>, int_bits: usize) -> Literal {
+        //! Joins the mask or code strings of all bytes into one literal of `int_bits` bits.
+        let mut bits: String = strings.collect();
+        bits.extend(std::iter::repeat_n('0', int_bits - bits.len()));
+        Literal::u128_unsuffixed(u128::from_str_radix(&bits, 2).unwrap())
+    }
+
+    fn build_word_var_decoders(
+        &self,
+        variables: &HashMap<char, (Ident, Type, Endian)>,
+        int_bits: usize,
+        word: &Word,
+    ) -> Vec<(Ident, TokenStream2)> {
+        //! Same as `build_var_decoders(..)` but extracts the variables from the whole opcode
+        //! loaded into `opcode_word` (see `build_word_load(..)`). Neighbouring bits are extracted
+        //! together even if they are in different bytes.
+        let mut var_decoders = vec![];
+        for (c, (ident, ty, endian)) in variables.iter() {
+            let mut parts = vec![];
+            let mut src_pos_iter = self
+                .get_position_map_of(*c, endian, word)
+                .map(|(tar_bit, (src_byte, src_bit))| {
+                    (tar_bit, int_bits - 1 - (src_byte * word.bits + src_bit))
+                })
+                .peekable();
+            while let Some((tar_bit, rshift)) = src_pos_iter.next() {
+                let mut num_bits = 1; //how many bits will be decoded by this mask
+                while src_pos_iter
+                    .peek()
+                    .is_some_and(|(next_tar_bit, next_rshift)| {
+                        *next_rshift == rshift + num_bits && *next_tar_bit == tar_bit + num_bits
+                    })
+                {
+                    let _ = src_pos_iter.next();
+                    num_bits += 1;
+                }
+                let mask = Literal::u128_unsuffixed((1u128 << num_bits) - 1);
+                parts.push(if *ty == parse_quote!(bool) {
+                    quote! { (opcode_word >> #rshift) & #mask != 0 }
+                } else {
+                    quote! { (((opcode_word >> #rshift) & #mask) as #ty) << #tar_bit }
+                });
+            }
+            var_decoders.push((ident.clone(), quote! { #(#parts)|* }));
+        }
+        var_decoders
+    }
+
+    fn build_var_decoders(
+        &self,
+        variables: &HashMap<char, (Ident, Type, Endian)>,
+        word: &Word,
+    ) -> Vec<(Ident, TokenStream2)> {
         //! This function takes a variable map from the corresponding instruction and for each
-        //! variable constructs a block that reads the corresponding bits in the memory, shifts
-        //! them in the right position and bitwise or's them all together.
+        //! variable constructs an expression that reads the corresponding bits in the memory,
+        //! shifts them in the right position and bitwise or's them all together. It returns the
+        //! identifier of each variable together with its expression.
         //! This function should always return a valid (in terms of parseability) `TokenStream2`
         //! but if it an unsupported type should turn up here (which would be a bug in
         //! `Instruction::parse()` this function will cause a compile error pointing at the
         //! violating variable
+        //! If the opcode can be loaded as a whole (see `opcode_word(..)`) the variables are
+        //! extracted from the loaded opcode instead.
+        if let Some((_, int_bits)) = self.opcode_word(word) {
+            return self.build_word_var_decoders(variables, int_bits, word);
+        }
         let mut var_decoders = vec![];
-        for (c, (ident, ty)) in variables.iter() {
+        for (c, (ident, ty, endian)) in variables.iter() {
             let mut masks = vec![];
             let mut src_bytes = vec![];
             let mut left_shifts = vec![];
             let mut right_shifts = vec![];
-            let mut src_pos_iter = self.get_position_map_of(*c).peekable();
+            let mut src_pos_iter = self.get_position_map_of(*c, endian, word).peekable();
             while let Some((tar_bit, (src_byte, src_bit))) = src_pos_iter.next() {
-                let mut mask = vec!['0', '0', '0', '0', '0', '0', '0', '0'];
+                let mut mask = vec!['0'; word.bits];
                 mask[src_bit] = '1';
 
                 let mut num_bits = 1; //how many bits will be decoded by this mask
                 loop {
                     let next_is_neighbour =
-                        src_pos_iter.peek().map_or(false, |(_, (byte, bit))| {
-                            *byte == src_byte && src_bit == *bit + num_bits
-                        });
+                        src_pos_iter
+                            .peek()
+                            .is_some_and(|(next_tar_bit, (byte, bit))| {
+                                *byte == src_byte
+                                    && src_bit == *bit + num_bits
+                                    && *next_tar_bit == tar_bit + num_bits
+                            });
                     if next_is_neighbour {
                         let (_, (_, next_src_bit)) = src_pos_iter.next().unwrap();
                         mask[next_src_bit] = '1';
@@ -547,41 +1690,45 @@ impl Opcode {
                 mask_str.extend(mask.iter());
                 masks.push(LitInt::new(&mask_str, self.span()));
                 src_bytes.push(src_byte);
-                right_shifts.push(7 - src_bit);
+                right_shifts.push(word.bits - 1 - src_bit);
                 left_shifts.push(tar_bit);
             }
-            var_decoders.push(
-                if *ty == parse_quote!(u8) ||
-                *ty == parse_quote!(u16) ||
-                *ty == parse_quote!(u32) ||
-                *ty == parse_quote!(u64) ||
-                *ty == parse_quote!(u128) ||
-                *ty == parse_quote!(usize) ||
-                *ty == parse_quote!(i8) ||
-                *ty == parse_quote!(i16) ||
-                *ty == parse_quote!(i32) ||
-                *ty == parse_quote!(i64) ||
-                *ty == parse_quote!(i128) ||
-                *ty == parse_quote!(isize) {
+            var_decoders.push((
+                ident.clone(),
+                if *ty == parse_quote!(u8)
+                    || *ty == parse_quote!(u16)
+                    || *ty == parse_quote!(u32)
+                    || *ty == parse_quote!(u64)
+                    || *ty == parse_quote!(u128)
+                    || *ty == parse_quote!(usize)
+                    || *ty == parse_quote!(i8)
+                    || *ty == parse_quote!(i16)
+                    || *ty == parse_quote!(i32)
+                    || *ty == parse_quote!(i64)
+                    || *ty == parse_quote!(i128)
+                    || *ty == parse_quote!(isize)
+                {
                     quote! {
-                        #ident: #((((mem[#src_bytes] & #masks) >> #right_shifts) as #ty) << #left_shifts)|*
+                        #((((mem[#src_bytes] & #masks) >> #right_shifts) as #ty) << #left_shifts)|*
                     }
                 } else if *ty == parse_quote!(bool) {
                     //#ident: #((mem[#src_bytes] >> #right_shifts) != 0)|*
-                    quote!{
-                        #ident: #((((mem[#src_bytes] & #masks) >> #right_shifts) != 0))|*
+                    quote! {
+                        #((((mem[#src_bytes] & #masks) >> #right_shifts) != 0))|*
                     }
                 } else {
                     Error::new(ty.span(), format!("Unsupported type {:?}", ty)).to_compile_error()
-            });
-        }
-
-        quote! {
-            #(#var_decoders),*
+                },
+            ));
         }
+        var_decoders
     }
 
-    fn build_encoder(&self, variables: &HashMap<char, (Ident, Type)>) -> TokenStream2 {
+    fn build_encoder(
+        &self,
+        variables: &HashMap<char, (Ident, Type, Endian)>,
+        word: &Word,
+    ) -> TokenStream2 {
         //! This function takes the variable map from the corresponding `Instruction` and
         //! constructs a decoder from this opcode to each variable and joins them to a variable
         //! decoder block that is used in the encoder function.
@@ -602,10 +1749,11 @@ impl Opcode {
         //!
         //! ```
         let code_bytes: Vec<LitInt> = self
-            .code_strings()
+            .code_strings(word)
             .map(|s| LitInt::new(&format!("0b{}", s), self.span()))
             .collect();
-        let num_bytes = self.bytes.len();
+        let num_bytes = self.num_words(word);
+        let word_ty = &word.ty;
         let code_indices = 0..num_bytes;
         let mut tokens = quote! {
                 if buf.len() < #num_bytes {
@@ -614,19 +1762,23 @@ impl Opcode {
                 #(buf[#code_indices] = #code_bytes);*;
         };
 
-        for (c, (ident, ty)) in variables.iter() {
-            let mut positions_iter = self.get_position_map_of(*c).peekable();
+        for (c, (ident, ty, endian)) in variables.iter() {
+            let mut positions_iter = self.get_position_map_of(*c, endian, word).peekable();
             while let Some((src_bit, (tar_byte, tar_bit))) = positions_iter.next() {
-                let lshift = 7 - tar_bit;
+                let lshift = word.bits - 1 - tar_bit;
                 let rshift = src_bit;
-                let mut mask = 1;
+                let mut mask: u128 = 1;
 
                 let mut num_bits = 1; //number of bits decoded by this mask
                 loop {
                     let next_is_neighbour =
-                        positions_iter.peek().map_or(false, |(_, (byte, bit))| {
-                            *byte == tar_byte && tar_bit == *bit + num_bits
-                        });
+                        positions_iter
+                            .peek()
+                            .is_some_and(|(next_src_bit, (byte, bit))| {
+                                *byte == tar_byte
+                                    && tar_bit == *bit + num_bits
+                                    && *next_src_bit == src_bit + num_bits
+                            });
                     if next_is_neighbour {
                         let _ = positions_iter.next().unwrap();
                         mask = (mask << 1) + 1;
@@ -650,27 +1802,157 @@ impl Opcode {
                         *ty == parse_quote!(i128) ||
                         *ty == parse_quote!(isize) {
                         quote! {
-                            buf[#tar_byte] |= (((#ident >> #rshift) & #mask as #ty) << #lshift) as ::std::primitive::u8;
+                            buf[#tar_byte] |= (((#ident >> #rshift) & #mask as #ty) as #word_ty) << #lshift;
                         }
                     } else if *ty == parse_quote!(bool) {
                         quote!{
-                            buf[#tar_byte] |= ((if *#ident {1} else {0}) << #lshift) as ::std::primitive::u8;
+                            buf[#tar_byte] |= ((if *#ident {1} else {0}) as #word_ty) << #lshift;
                         }
                     } else {
                         Error::new(ty.span(), format!("Unsupported type {:?}", ty)).to_compile_error()
                 });
             }
         }
-        tokens.extend(quote! {return Ok(#num_bytes);});
         tokens
     }
 
-    pub(crate) fn build_match_conditions(&self) -> TokenStream2 {
+    fn build_slot_decoders(
+        &self,
+        slots: &HashMap<char, (Ident, Type)>,
+        word: &Word,
+    ) -> Vec<(Ident, TokenStream2)> {
+        //! For each slot constructs an expression that decodes the instruction in the slot. The
+        //! bits of the slot are gathered in opcode order into a window of bytes so that the slot
+        //! ends with the window. The slot's instruction is then decoded from the bit stream in
+        //! the window (i.e. with `decode_bits(..)`) and can't read past the end of the slot.
+        let mut slot_decoders = vec![];
+        for (c, (ident, ty)) in slots.iter() {
+            let num_bits = self.bits.iter().filter(|&bit| bit == c).count();
+            let window_len = num_bits.div_ceil(8);
+            let start = 8 * window_len - num_bits;
+            let mut gather = vec![];
+            for (tar_bit, (src_word, src_bit)) in self.get_position_map_of(*c, &Endian::Big, word) {
+                let tar_byte = window_len - 1 - tar_bit / 8;
+                let lshift = tar_bit % 8;
+                let rshift = word.bits - 1 - src_bit;
+                gather.push(quote! {
+                    slot_window[#tar_byte] |= (((mem[#src_word] >> #rshift) & 1) as ::std::primitive::u8) << #lshift;
+                });
+            }
+            slot_decoders.push((
+                ident.clone(),
+                quote! {
+                    {
+                        let mut slot_window = [0u8; #window_len];
+                        #(#gather)*
+                        <#ty>::decode_bits(&slot_window, #start)?.1
+                    }
+                },
+            ));
+        }
+        slot_decoders
+    }
+
+    fn build_slot_encoders(
+        &self,
+        slots: &HashMap<char, (Ident, Type)>,
+        word: &Word,
+    ) -> TokenStream2 {
+        //! Encodes the instruction in each slot into a window of bytes that ends with the slot
+        //! (see `build_slot_decoders(..)`) and scatters the bits of the window over the
+        //! positions of the slot in the opcode. Bits of the slot that are not used by its
+        //! instruction are zero.
+        let mut tokens = TokenStream2::new();
+        let word_ty = &word.ty;
+        for (c, (ident, _)) in slots.iter() {
+            let num_bits = self.bits.iter().filter(|&bit| bit == c).count();
+            let window_len = num_bits.div_ceil(8);
+            let start = 8 * window_len - num_bits;
+            let mut scatter = vec![];
+            for (src_bit, (tar_word, tar_bit)) in self.get_position_map_of(*c, &Endian::Big, word) {
+                let src_byte = window_len - 1 - src_bit / 8;
+                let rshift = src_bit % 8;
+                let lshift = word.bits - 1 - tar_bit;
+                scatter.push(quote! {
+                    buf[#tar_word] |= (((slot_window[#src_byte] >> #rshift) & 1) as #word_ty) << #lshift;
+                });
+            }
+            tokens.extend(quote! {
+                {
+                    let mut slot_window = [0u8; #window_len];
+                    #ident.encode_bits(&mut slot_window, #start)?;
+                    #(#scatter)*
+                }
+            });
+        }
+        tokens
+    }
+
+    fn build_store(&self, word: &Word) -> TokenStream2 {
+        //! When encoding into a bit stream the opcode is encoded into `buf` which is only a window
+        //! of whole bytes. This copies the bits of the opcode into the bit stream `bits_buf`.
+        if !word.bitstream {
+            return TokenStream2::new();
+        }
+        let num_bits = self.num_bits();
+        quote! {
+            imperative_rs::bits::write(bits_buf, bit_offset, buf, #num_bits)?;
+        }
+    }
+
+    pub(crate) fn truncated_condition(&self, word: &Word) -> TokenStream2 {
+        //! Returns an expression that evaluates to `true` when the memory ends before this opcode
+        //! does, but every word it holds still matches the opcode.
+        let num_bytes = self.num_words(word);
+        let mut tokens = if word.bitstream {
+            let num_bits = self.num_bits();
+            quote! { available_bits < #num_bits }
+        } else {
+            quote! { mem.len() < #num_bytes }
+        };
+        for (idx, (code_str, mask_str)) in self
+            .code_strings(word)
+            .zip(self.mask_strings(word))
+            .enumerate()
+        {
+            let mask = LitInt::new(&format!("0b{}", mask_str), self.span);
+            let code = LitInt::new(&format!("0b{}", code_str), self.span);
+            let missing = if word.bitstream {
+                let end_bit = (idx + 1) * word.bits;
+                quote! { available_bits < #end_bit }
+            } else if idx == 0 {
+                quote! { mem.is_empty() }
+            } else {
+                quote! { mem.len() <= #idx }
+            };
+            tokens.extend(quote! {
+                && (#missing || mem[#idx] & #mask == #code)
+            });
+        }
+        tokens
+    }
+
+    pub(crate) fn build_match_conditions(&self, word: &Word) -> TokenStream2 {
         //! Puts together mask and code strings to produce an expression that evaluates to `true`
         //! when and only when the memory contains this opcode
-        let num_bytes = self.bytes.len();
-        let mut tokens = quote! { mem.len() >= #num_bytes };
-        for (idx, (code_str, mask_str)) in self.code_strings().zip(self.mask_strings()).enumerate()
+        let num_bytes = self.num_words(word);
+        if let Some((load, int_bits)) = self.opcode_word(word) {
+            let mask = self.word_literal(self.mask_strings(word), int_bits);
+            let code = self.word_literal(self.code_strings(word), int_bits);
+            return quote! {
+                mem.len() >= #num_bytes && #load & #mask == #code
+            };
+        }
+        let mut tokens = if word.bitstream {
+            let num_bits = self.num_bits();
+            quote! { available_bits >= #num_bits }
+        } else {
+            quote! { mem.len() >= #num_bytes }
+        };
+        for (idx, (code_str, mask_str)) in self
+            .code_strings(word)
+            .zip(self.mask_strings(word))
+            .enumerate()
         {
             let mask = LitInt::new(&format!("0b{}", mask_str), self.span);
             let code = LitInt::new(&format!("0b{}", code_str), self.span);
@@ -696,57 +1978,110 @@ impl Parse for Opcode {
         if prefix.len() != 2 || prefix[0] != '0' || (prefix[1] != 'x' && prefix[1] != 'b') {
             Err(Error::new(literal.span(), "Invalid opcode. Valid opcodes start with either '0x' or '0b' followed by at least one digit/variable"))
         } else {
-            let code: Vec<char> = if prefix[1] == 'x' {
+            let bits: Vec<char> = if prefix[1] == 'x' {
                 hex_to_bin_string(&literal_string).chars().collect()
             } else {
                 literal_string.chars().skip(2).collect()
             };
-            let mut bytes = vec![];
-            for (pos, c) in code.iter().enumerate() {
-                let bit_idx = pos % 8;
-                let byte_idx = (pos - bit_idx) / 8;
-                if bytes.len() <= byte_idx {
-                    bytes.push(['0'; 8]);
-                }
-                bytes[byte_idx][bit_idx] = *c;
-            }
             Ok(Opcode {
-                bytes,
+                bits,
                 span: literal.span(),
             })
         }
     }
 }
 
-/// This type implements collision detection between opcodes. With the new matcher this type is
-/// almost obsolete and might be refactored soon
-pub(crate) struct CollisionGuard<'a>(Vec<&'a Opcode>);
+/// This type implements collision detection between opcodes. Opcodes are compared as they are
+/// laid out in a memory of the given `Word`. Two opcodes collide if every bit they both have is
+/// either the same or variable in one of them. Instead of comparing all pairs of opcodes, the
+/// opcodes are split on their constant bits first, like the decoder does. Only opcodes that end
+/// up in the same part can collide.
+pub(crate) struct CollisionGuard(Vec<Vec<char>>);
 
-impl<'a> CollisionGuard<'a> {
-    pub(crate) fn new() -> Self {
-        CollisionGuard(vec![])
+impl CollisionGuard {
+    /// Parts with at most this many opcodes are compared pairwise.
+    const MAX_PAIRWISE: usize = 8;
+
+    pub(crate) fn new(opcodes: &[&Opcode], word: &Word) -> Self {
+        CollisionGuard(
+            opcodes
+                .iter()
+                .map(|opcode| opcode.collision_iter(word).collect())
+                .collect(),
+        )
     }
 
-    pub(crate) fn is_collision(op1: &Opcode, op2: &Opcode) -> bool {
-        for (c1, c2) in op1.collision_iter().zip(op2.collision_iter()) {
-            if c1 != '*' && c2 != '*' {
-                if c1 != c2 {
-                    return false;
-                } else {
-                    continue;
+    pub(crate) fn first_collision(&self) -> Option<(usize, usize)> {
+        //! Returns the indices of the first opcode that collides with an opcode before it and of
+        //! the first opcode before it that it collides with.
+        let mut first = None;
+        self.find_collisions((0..self.0.len()).collect(), &mut first);
+        first
+    }
+
+    fn is_collision(&self, idx1: usize, idx2: usize) -> bool {
+        self.0[idx1]
+            .iter()
+            .zip(&self.0[idx2])
+            .all(|(c1, c2)| c1 == c2 || *c1 == '*' || *c2 == '*')
+    }
+
+    fn find_collisions(&self, part: Vec<usize>, first: &mut Option<(usize, usize)>) {
+        //! Splits the part on the bit that is '0' in as many opcodes as it is '1'. Opcodes that
+        //! are variable in this bit are put into both halves (see `CollisionGuard::bit(..)`). If no bit is '0' in some opcodes
+        //! and '1' in others, all opcodes of the part collide.
+        if part.len() <= Self::MAX_PAIRWISE {
+            for (later_idx, later) in part.iter().enumerate() {
+                for earlier in &part[..later_idx] {
+                    if self.is_collision(*later, *earlier) {
+                        Self::update_first(first, (*later, *earlier));
+                    }
                 }
             }
+            return;
+        }
+        let max_len = part.iter().map(|idx| self.0[*idx].len()).max().unwrap();
+        let split = (0..max_len)
+            .map(|bit_idx| {
+                let zeros = part
+                    .iter()
+                    .filter(|idx| self.bit(**idx, bit_idx) == '0')
+                    .count();
+                let ones = part
+                    .iter()
+                    .filter(|idx| self.bit(**idx, bit_idx) == '1')
+                    .count();
+                (zeros.min(ones), bit_idx)
+            })
+            .max();
+        match split {
+            Some((balance, bit_idx)) if balance > 0 => {
+                let zeros = part
+                    .iter()
+                    .copied()
+                    .filter(|idx| self.bit(*idx, bit_idx) != '1')
+                    .collect();
+                let ones = part
+                    .iter()
+                    .copied()
+                    .filter(|idx| self.bit(*idx, bit_idx) != '0')
+                    .collect();
+                self.find_collisions(zeros, first);
+                self.find_collisions(ones, first);
+            }
+            _ => Self::update_first(first, (part[1], part[0])),
         }
-        true
     }
 
-    pub(crate) fn collides_or_insert(&mut self, opcode: &'a Opcode) -> Option<&'a Opcode> {
-        for ex_opcode in &self.0 {
-            if CollisionGuard::is_collision(opcode, ex_opcode) {
-                return Some(ex_opcode);
-            }
+    fn bit(&self, idx: usize, bit_idx: usize) -> char {
+        //! Opcodes are only compared as long as both have bits. So the bits after the end of an
+        //! opcode are variable.
+        self.0[idx].get(bit_idx).copied().unwrap_or('*')
+    }
+
+    fn update_first(first: &mut Option<(usize, usize)>, collision: (usize, usize)) {
+        if first.is_none_or(|first| collision < first) {
+            *first = Some(collision);
         }
-        self.0.push(opcode);
-        None
     }
 }
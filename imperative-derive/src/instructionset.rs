@@ -1,65 +1,1170 @@
-use crate::instruction::{CollisionGuard, Instruction};
+use crate::instruction::{CollisionGuard, Instruction, Opcode, TrailingField, Word};
+use crate::matcher::{Decoder, MatchTree, Optimize, TooManyArms, DEFAULT_MAX_ARMS};
+use proc_macro2::Span;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
+use std::path::PathBuf;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::Result as SynResult;
-use syn::{braced, Attribute, Error, Generics, Ident, Token, Visibility};
+use syn::{
+    braced, Attribute, Error, Expr, Generics, Ident, Lit, LitInt, LitStr, Meta, NestedMeta, Token,
+    Type, Visibility,
+};
 
 pub(crate) struct InstructionSet {
+    vis: Visibility,
     ident: Ident,
     generics: Generics,
     instructions: Punctuated<Instruction, Token!(,)>,
+    attrs: SetAttrs,
+    trees: Trees,
+}
+
+/// The decoder trees of an instruction set. They are built once while parsing (see
+/// `InstructionSet::check_arms(..)`) and turned into tokens by every function that decodes their
+/// instructions. A tree is `None` if there are no instructions to decode.
+/// * `modes` decode bytes, one tree for each mode in the order of `InstructionSet::modes(..)`.
+///   The first one decodes the instructions without a condition.
+/// * `word` decodes the words given by `#[imperative(word = "..")]`.
+/// * `bitstream` decodes the window of a bit stream.
+///
+/// The trees refer to their leaves by the index in the list of instructions of their mode (see
+/// `InstructionSet::in_mode(..)`).
+struct Trees {
+    modes: Vec<Option<MatchTree>>,
+    word: Option<MatchTree>,
+    bitstream: Option<MatchTree>,
+}
+
+/// This struct collects the options given to the whole instruction set by
+/// `#[imperative(..)]` on the `enum`.
+/// * `context` is the type given by `#[imperative(context = "Mode")]`. It is the type of the
+///   `ctx` argument of the generated `decode_with(mem, ctx)` and enables `#[when = ".."]` on
+///   variants.
+/// * `word` is the word type given by `#[imperative(word = "u16")]`. It enables decoding from
+///   and encoding into word memories through `WordInstructionSet`. With
+///   `#[imperative(word = "u16", word_bits = 12)]` each word only holds 12 bits (e.g. PDP-8).
+/// * `bitstream` is set by `#[imperative(bitstream)]`. It generates `decode_bits(mem, bit_offset)`
+///   and `encode_bits(buf, bit_offset)` for instructions that are not aligned to bytes.
+/// * `kind` is the name given by `#[imperative(kind = "OpKind")]`. A fieldless enum of this name
+///   with one variant per instruction is generated together with `classify(mem)` and
+///   `kind(&self)`.
+/// * `decoder` is the backend selected by `#[imperative(decoder = "table")]`. It defaults to
+///   `Decoder::Tree`.
+/// * `profile` is the path given by `#[imperative(profile = "cpu.profile")]`. The file holds the
+///   weights of the instructions (see `InstructionSet::apply_profile(..)`).
+/// * `max_arms` is the number of match arms a decoder may consist of. It is given by
+///   `#[imperative(max_arms = 100000)]` and defaults to `DEFAULT_MAX_ARMS`.
+/// * `optimize` is the goal selected by `#[imperative(optimize = "size")]`. It defaults to
+///   `Optimize::Speed`.
+struct SetAttrs {
+    context: Option<Type>,
+    word: Option<Word>,
+    bitstream: bool,
+    kind: Option<Ident>,
+    decoder: Decoder,
+    profile: Option<LitStr>,
+    max_arms: usize,
+    optimize: Optimize,
+}
+
+impl SetAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> SynResult<SetAttrs> {
+        let mut set_attrs = SetAttrs {
+            context: None,
+            word: None,
+            bitstream: false,
+            kind: None,
+            decoder: Decoder::Tree,
+            profile: None,
+            max_arms: DEFAULT_MAX_ARMS,
+            optimize: Optimize::Speed,
+        };
+        let mut word_bits: Option<LitInt> = None;
+        for attribute in attrs {
+            if !attribute.path.is_ident("imperative") {
+                continue;
+            }
+            let meta = attribute.parse_meta()?;
+            let list = match meta {
+                Meta::List(list) => list,
+                _ => return Err(Error::new(meta.span(), "Instruction set options need to be given as a list (e.g. #[imperative(context = \"Mode\")])")),
+            };
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("context") =>
+                    {
+                        match name_value.lit {
+                            Lit::Str(str_lit) => set_attrs.context = Some(str_lit.parse()?),
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "The decode context must be given as a type in a string literal (e.g. context = \"Mode\")",
+                                ))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("word") =>
+                    {
+                        let bits = match &name_value.lit {
+                            Lit::Str(str_lit) if str_lit.value() == "u16" => 16,
+                            Lit::Str(str_lit) if str_lit.value() == "u32" => 32,
+                            Lit::Str(str_lit) if str_lit.value() == "u64" => 64,
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "Unsupported word type. Supported word types are: \"u16\", \"u32\", \"u64\" (bytes are decoded by InstructionSet::decode)",
+                                ))
+                            }
+                        };
+                        let ty = Ident::new(&format!("u{}", bits), Span::call_site());
+                        let word = Word {
+                            bits,
+                            ty: quote!(::std::primitive::#ty),
+                            instr_word_bits: None,
+                            bitstream: false,
+                        };
+                        set_attrs.word = Some(word);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("word_bits") =>
+                    {
+                        match name_value.lit {
+                            Lit::Int(int_lit) => word_bits = Some(int_lit),
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "The word width must be given as an integer (e.g. word_bits = 12)",
+                                ))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("bitstream") => {
+                        set_attrs.bitstream = true;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("decoder") =>
+                    {
+                        set_attrs.decoder = match &name_value.lit {
+                            Lit::Str(str_lit) if str_lit.value() == "tree" => Decoder::Tree,
+                            Lit::Str(str_lit) if str_lit.value() == "table" => Decoder::Table,
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "Unknown decoder. Supported decoders are: \"tree\", \"table\"",
+                                ))
+                            }
+                        };
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("kind") =>
+                    {
+                        match name_value.lit {
+                            Lit::Str(str_lit) => set_attrs.kind = Some(str_lit.parse()?),
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "The name of the kind enum must be given as a string literal (e.g. kind = \"OpKind\")",
+                                ))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("profile") =>
+                    {
+                        match name_value.lit {
+                            Lit::Str(str_lit) => set_attrs.profile = Some(str_lit),
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "The profile must be given as a path in a string literal (e.g. profile = \"cpu.profile\")",
+                                ))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("max_arms") =>
+                    {
+                        match name_value.lit {
+                            Lit::Int(int_lit) => set_attrs.max_arms = int_lit.base10_parse()?,
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "The number of match arms must be given as an integer (e.g. max_arms = 100000)",
+                                ))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("optimize") =>
+                    {
+                        set_attrs.optimize = match &name_value.lit {
+                            Lit::Str(str_lit) if str_lit.value() == "speed" => Optimize::Speed,
+                            Lit::Str(str_lit) if str_lit.value() == "size" => Optimize::Size,
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "Unknown optimization goal. Supported goals are: \"speed\", \"size\"",
+                                ))
+                            }
+                        };
+                    }
+                    other => {
+                        return Err(Error::new(
+                            other.span(),
+                            "Unknown instruction set option. Supported options are: bitstream, context, decoder, kind, max_arms, optimize, profile, word, word_bits",
+                        ))
+                    }
+                }
+            }
+        }
+        if let Some(word_bits) = word_bits {
+            let word = match set_attrs.word.as_mut() {
+                Some(word) => word,
+                None => {
+                    return Err(Error::new(
+                        word_bits.span(),
+                        "A word width needs a word type to store the words in (e.g. #[imperative(word = \"u16\", word_bits = 12)])",
+                    ))
+                }
+            };
+            let bits: usize = word_bits.base10_parse()?;
+            if bits == 0 || bits > word.bits {
+                return Err(Error::new(
+                    word_bits.span(),
+                    format!(
+                        "The word width needs to be between 1 and {} bits to fit into the word type",
+                        word.bits
+                    ),
+                ));
+            }
+            word.bits = bits;
+        }
+        Ok(set_attrs)
+    }
+
+    fn byte_word(&self) -> Word {
+        //! Returns how the opcodes are laid out in bytes for `InstructionSet::decode(..)`. If
+        //! the instruction set has a word type each instruction word is stored in whole bytes.
+        let mut byte = Word::byte();
+        byte.instr_word_bits = self.word.as_ref().map(|word| word.bits);
+        byte
+    }
 }
 
 impl Parse for InstructionSet {
     fn parse(input: ParseStream) -> SynResult<Self> {
-        let _ = input.call(Attribute::parse_outer)?;
-        let _ = Visibility::parse(input)?;
+        let attrs = SetAttrs::from_attrs(&input.call(Attribute::parse_outer)?)?;
+        let vis = Visibility::parse(input)?;
         let _: Token!(enum) = input.parse()?;
         let ident = Ident::parse(input)?;
         let generics = Generics::parse(input)?;
         let content;
         let _ = braced!(content in input);
-        let instructions = content.parse_terminated(Instruction::parse)?;
-        let mut cg = CollisionGuard::new();
-        for instr in &instructions {
-            if let Some(colliding_opcode) = cg.collides_or_insert(&instr.opcode()) {
-                let mut err =
-                    syn::Error::new(instr.opcode().span(), "Opcode collides with other opdcode");
-                let other = Error::new(colliding_opcode.span(), "Collides with this opcode");
-                err.combine(other);
-                return Err(err);
+        let mut instructions = content.parse_terminated(Instruction::parse)?;
+        if let Some(profile) = attrs.profile.as_ref() {
+            Self::apply_profile(&mut instructions, profile)?;
+        }
+        Self::check_conditions(&instructions, &attrs)?;
+        //opcodes only need to be distinguishable from opcodes that can be decoded in the same mode
+        let instr_list: Vec<&Instruction> = instructions.iter().collect();
+        //in a bit stream opcodes are not padded, so none of them may be a prefix of another one
+        let mut words = vec![attrs.byte_word()];
+        if attrs.bitstream {
+            words.push(Word::bitstream());
+        }
+        for word in &words {
+            for mode in Self::modes(&instr_list) {
+                let instructions = Self::in_mode(&instr_list, mode);
+                let opcodes: Vec<&Opcode> =
+                    instructions.iter().map(|instr| instr.opcode()).collect();
+                if let Some((instr_idx, colliding_idx)) =
+                    CollisionGuard::new(&opcodes, word).first_collision()
+                {
+                    let mut err = syn::Error::new(
+                        opcodes[instr_idx].span(),
+                        "Opcode collides with other opdcode",
+                    );
+                    let other =
+                        Error::new(opcodes[colliding_idx].span(), "Collides with this opcode");
+                    err.combine(other);
+                    return Err(err);
+                }
             }
         }
+        Self::check_cycles(&instructions)?;
+        Self::check_extensions(&instructions)?;
+        Self::check_words(&instructions, &attrs)?;
+        let trees = Self::check_arms(&ident, &instructions, &attrs)?;
         Ok(InstructionSet {
+            vis,
             ident,
             generics,
             instructions,
+            attrs,
+            trees,
+        })
+    }
+}
+
+impl InstructionSet {
+    fn modes<'a>(instructions: &[&'a Instruction]) -> Vec<Option<&'a Expr>> {
+        //! Returns the distinct `#[when = ".."]` conditions in order of appearance. The first
+        //! element is always `None` which is the mode for instructions without any condition.
+        let mut modes: Vec<Option<&Expr>> = vec![None];
+        for instr in instructions {
+            if let Some(when) = instr.attrs().when.as_ref() {
+                if !modes
+                    .iter()
+                    .flatten()
+                    .any(|mode| Self::same_condition(mode, when))
+                {
+                    modes.push(Some(when));
+                }
+            }
+        }
+        modes
+    }
+
+    fn in_mode<'a>(instructions: &[&'a Instruction], mode: Option<&Expr>) -> Vec<&'a Instruction> {
+        //! Returns all instructions that can be decoded in the given mode. These are the
+        //! instructions without condition and the instructions with exactly this condition.
+        instructions
+            .iter()
+            .filter(|instr| match (instr.attrs().when.as_ref(), mode) {
+                (None, _) => true,
+                (Some(when), Some(mode)) => Self::same_condition(when, mode),
+                (Some(_), None) => false,
+            })
+            .copied()
+            .collect()
+    }
+
+    fn profile_path(profile: &LitStr) -> PathBuf {
+        //! Profiles are located relative to the manifest of the crate deriving the instruction
+        //! set, like the paths of `include_bytes!(..)` in build scripts.
+        let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+        PathBuf::from(manifest_dir).join(profile.value())
+    }
+
+    fn apply_profile(
+        instructions: &mut Punctuated<Instruction, Token!(,)>,
+        profile: &LitStr,
+    ) -> SynResult<()> {
+        //! Reads the weights of the instructions from the profile. Each line holds the name of an
+        //! instruction and its weight separated by whitespace or a comma (e.g. `Mov 1500`), so
+        //! execution histograms can be used as they are. Empty lines and lines starting with `#`
+        //! are skipped. The weight of `#[weight = ..]` takes precedence over the profile and
+        //! instructions missing from the profile keep the default weight.
+        let path = Self::profile_path(profile);
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            Error::new(
+                profile.span(),
+                format!("Failed to read profile {}: {}", path.display(), err),
+            )
+        })?;
+        for (line_idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry: Vec<&str> = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .collect();
+            let weight = match entry.as_slice() {
+                [_, weight] => weight.parse::<u64>().ok(),
+                _ => None,
+            };
+            let weight = match weight {
+                Some(weight) => weight,
+                None => {
+                    return Err(Error::new(
+                        profile.span(),
+                        format!(
+                            "Line {} of the profile is not an instruction name followed by its weight (e.g. Mov 1500)",
+                            line_idx + 1
+                        ),
+                    ))
+                }
+            };
+            let instr = match instructions
+                .iter_mut()
+                .find(|instr| instr.ident() == entry[0])
+            {
+                Some(instr) => instr,
+                None => {
+                    return Err(Error::new(
+                        profile.span(),
+                        format!(
+                            "Line {} of the profile names the unknown instruction {}",
+                            line_idx + 1,
+                            entry[0]
+                        ),
+                    ))
+                }
+            };
+            let attrs = instr.attrs_mut();
+            attrs.weight = attrs.weight.or(Some(weight));
+        }
+        Ok(())
+    }
+
+    fn profile_dependency(&self) -> Option<TokenStream2> {
+        //! Including the profile makes cargo rebuild the instruction set when the profile
+        //! changes.
+        self.attrs.profile.as_ref().map(|profile| {
+            let path = Self::profile_path(profile).display().to_string();
+            quote! {
+                const _: &[::std::primitive::u8] = ::std::include_bytes!(#path);
+            }
+        })
+    }
+
+    fn same_condition(lhs: &Expr, rhs: &Expr) -> bool {
+        lhs.to_token_stream().to_string() == rhs.to_token_stream().to_string()
+    }
+
+    fn check_conditions(
+        instructions: &Punctuated<Instruction, Token!(,)>,
+        attrs: &SetAttrs,
+    ) -> SynResult<()> {
+        //! `#[when = ".."]` conditions are evaluated on the context passed to `decode_with(..)`.
+        //! So they can only be used if the type of the context is defined.
+        if attrs.context.is_some() {
+            return Ok(());
+        }
+        let mut res: SynResult<()> = Ok(());
+        for instr in instructions {
+            if let Some(when) = instr.attrs().when.as_ref() {
+                let err = Error::new(when.span(), format!("Instruction {} is only decoded in some modes but no decode context is defined. Define it by adding #[imperative(context = \"..\")] to the enum", instr.ident()));
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+        }
+        res
+    }
+
+    fn matcher(
+        &self,
+        tree: Option<&MatchTree>,
+        instructions: &[&Instruction],
+        word: &Word,
+    ) -> TokenStream2 {
+        //! Constructs the decoder of `tree` from a memory of `word`s. `instructions` are the
+        //! instructions the tree was built from.
+        let ident = &self.ident;
+        let generics = &self.generics;
+        self.matcher_with(
+            tree,
+            instructions,
+            word,
+            quote! { (::std::primitive::usize, #ident#generics) },
+            &|instr| instr.decoder(word),
+        )
+    }
+
+    fn matcher_with(
+        &self,
+        tree: Option<&MatchTree>,
+        instructions: &[&Instruction],
+        word: &Word,
+        ret: TokenStream2,
+        leaf: &dyn Fn(&Instruction) -> TokenStream2,
+    ) -> TokenStream2 {
+        //! Constructs the decoder of `tree` from a memory of `word`s whose leaves are constructed
+        //! by `leaf` and evaluate to `Result<ret, DecodeError>`.
+        Self::tree_tokens(tree, |tree, tokens| {
+            tree.to_tokens_with(tokens, instructions, word, self.attrs.optimize, &ret, leaf)
+        })
+    }
+
+    fn unchecked_matcher_with(
+        &self,
+        tree: Option<&MatchTree>,
+        instructions: &[&Instruction],
+        word: &Word,
+        ret: TokenStream2,
+        leaf: &dyn Fn(&Instruction) -> TokenStream2,
+    ) -> TokenStream2 {
+        //! Constructs the decoder of `tree` like `matcher_with(..)` for a memory that is known to
+        //! hold the longest opcode.
+        Self::tree_tokens(tree, |tree, tokens| {
+            tree.to_unchecked_tokens_with(
+                tokens,
+                instructions,
+                word,
+                self.attrs.optimize,
+                &ret,
+                leaf,
+            )
+        })
+    }
+
+    fn tree_tokens(
+        tree: Option<&MatchTree>,
+        build: impl FnOnce(&MatchTree, &mut TokenStream2),
+    ) -> TokenStream2 {
+        //! Constructs the tokens of a tree with `build`. Trees without instructions know no
+        //! opcode.
+        match tree {
+            Some(tree) => {
+                let mut tokens = TokenStream2::new();
+                build(tree, &mut tokens);
+                tokens
+            }
+            None => quote! { Err(imperative_rs::DecodeError::UnknownOpcode) },
+        }
+    }
+
+    fn default_instructions(&self) -> Vec<&Instruction> {
+        //! Returns the instructions without a condition. They are decoded by the trees of all
+        //! memories and by `default_tree()`.
+        let instr_list: Vec<&Instruction> = self.instructions.iter().collect();
+        Self::in_mode(&instr_list, None)
+    }
+
+    fn default_tree(&self) -> Option<&MatchTree> {
+        //! Returns the tree that decodes the instructions without a condition from bytes. It is
+        //! shared by all functions that don't decode a mode.
+        self.trees.modes[0].as_ref()
+    }
+
+    fn too_many_arms_error(ident: &Ident, too_many_arms: &TooManyArms, max_arms: usize) -> Error {
+        Error::new(
+            ident.span(),
+            format!(
+                "The decoder of {} needs more than {} match arms. Instruction {} is part of {} of them, instructions are copied into multiple arms if they have variables in the bits that tell the other instructions apart. Try #[imperative(decoder = \"table\")] or raise the limit with #[imperative(max_arms = ..)]",
+                ident,
+                max_arms,
+                too_many_arms.instr.ident(),
+                too_many_arms.num_arms
+            ),
+        )
+    }
+
+    fn check_arms(
+        ident: &Ident,
+        instructions: &Punctuated<Instruction, Token!(,)>,
+        attrs: &SetAttrs,
+    ) -> SynResult<Trees> {
+        //! Builds the decoders of all modes and memories, so the size of the decoders is reported
+        //! as an error on the enum instead of in the generated code. The trees are kept to
+        //! construct the decoders.
+        let build = |instructions: &[&Instruction], word: &Word| -> SynResult<Option<MatchTree>> {
+            if instructions.is_empty() {
+                return Ok(None);
+            }
+            MatchTree::from_list(instructions, word, attrs.decoder, attrs.max_arms)
+                .map(Some)
+                .map_err(|too_many_arms| {
+                    Self::too_many_arms_error(ident, &too_many_arms, attrs.max_arms)
+                })
+        };
+        let instr_list: Vec<&Instruction> = instructions.iter().collect();
+        let default_instructions = Self::in_mode(&instr_list, None);
+        let modes = Self::modes(&instr_list)
+            .into_iter()
+            .map(|mode| build(&Self::in_mode(&instr_list, mode), &attrs.byte_word()))
+            .collect::<SynResult<_>>()?;
+        let word = match attrs.word.as_ref() {
+            Some(word) => build(&default_instructions, word)?,
+            None => None,
+        };
+        let bitstream = if attrs.bitstream {
+            build(&default_instructions, &Word::bitstream())?
+        } else {
+            None
+        };
+        Ok(Trees {
+            modes,
+            word,
+            bitstream,
+        })
+    }
+
+    fn check_words(
+        instructions: &Punctuated<Instruction, Token!(,)>,
+        attrs: &SetAttrs,
+    ) -> SynResult<()> {
+        //! Decoding from words needs opcodes that consist of whole words. Fields that are
+        //! decoded from bytes (i.e. payloads and varints) can't be decoded from words.
+        let word = match attrs.word.as_ref() {
+            Some(word) => word,
+            None => return Ok(()),
+        };
+        let mut res: SynResult<()> = Ok(());
+        for instr in instructions {
+            if instr.opcode().num_bits() % word.bits != 0 {
+                let err = Error::new(
+                    instr.opcode().span(),
+                    format!(
+                        "Opcode of Instruction {} has {} bits which are not a multiple of the word size ({} bits)",
+                        instr.ident(),
+                        instr.opcode().num_bits(),
+                        word.bits
+                    ),
+                );
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+            for field in instr
+                .trailing()
+                .iter()
+                .filter(|field| field.is_byte_oriented())
+            {
+                let err = Error::new(
+                    field.ident().span(),
+                    "Payloads and varints are decoded from bytes and can't be used with #[imperative(word = \"..\")]",
+                );
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+        }
+        res
+    }
+
+    fn word_impl(&self) -> Option<TokenStream2> {
+        //! Implements `WordInstructionSet` for the word type given by
+        //! `#[imperative(word = "..")]`. It decodes the same instructions as
+        //! `InstructionSet::decode(..)`.
+        let word = self.attrs.word.as_ref()?;
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let word_ty = &word.ty;
+        let matcher = self.matcher(self.trees.word.as_ref(), &self.default_instructions(), word);
+        let encode_blocks = self
+            .instructions
+            .iter()
+            .map(|instr| instr.encoder_block(word));
+        Some(quote! {
+            #[automatically_derived]
+            #[allow(unused_qualifications)]
+            impl#generics imperative_rs::WordInstructionSet<#word_ty> for #ident#generics {
+                fn decode_words(mem:&[#word_ty]) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                    #matcher
+                }
+
+                fn encode_words(&self, buf:&mut [#word_ty]) -> ::std::result::Result<::std::primitive::usize, imperative_rs::EncodeError> {
+                    match self {
+                        #(#encode_blocks)*
+                    }
+                }
+            }
+        })
+    }
+
+    fn length_fns(&self) -> TokenStream2 {
+        //! Generates `InstructionSet::instruction_len(mem)` and the constants `MIN_LEN` and
+        //! `MAX_LEN` of the trait. The length is found by the same matcher as
+        //! `InstructionSet::decode(..)` but the leaves only decode what is needed to know the
+        //! length of the trailing fields.
+        let word = self.attrs.byte_word();
+        let matcher = self.matcher_with(
+            self.default_tree(),
+            &self.default_instructions(),
+            &word,
+            quote! { ::std::primitive::usize },
+            &|instr| instr.length_decoder(&word),
+        );
+        let min_lens = self.instructions.iter().map(|instr| instr.min_len(&word));
+        let max_lens = self.instructions.iter().map(|instr| instr.max_len(&word));
+        let min_init = if self.instructions.is_empty() {
+            quote! { 0 }
+        } else {
+            quote! { ::std::primitive::usize::MAX }
+        };
+        quote! {
+            const MIN_LEN: ::std::primitive::usize = {
+                let mut min = #min_init;
+                #({
+                    let len = #min_lens;
+                    if len < min {
+                        min = len;
+                    }
+                })*
+                min
+            };
+
+            const MAX_LEN: ::std::option::Option<::std::primitive::usize> = {
+                let mut max = Some(0);
+                #({
+                    max = match (max, #max_lens) {
+                        (Some(max), Some(len)) => Some(if len > max { len } else { max }),
+                        _ => None,
+                    };
+                })*
+                max
+            };
+
+            #[allow(unused_variables)]
+            fn instruction_len(mem:&[::std::primitive::u8]) -> ::std::result::Result<::std::primitive::usize, imperative_rs::DecodeError> {
+                #matcher
+            }
+        }
+    }
+
+    fn kind_enum(&self) -> Option<TokenStream2> {
+        //! Generates the fieldless enum named by `#[imperative(kind = "..")]`. Its variants are
+        //! ordered like the instructions and `ALL` lists them in this order.
+        let kind = self.attrs.kind.as_ref()?;
+        let vis = &self.vis;
+        let doc = format!("The kinds of instructions of `{}`.", self.ident);
+        let variants: Vec<&Ident> = self
+            .instructions
+            .iter()
+            .map(|instr| instr.ident())
+            .collect();
+        let variant_docs = variants
+            .iter()
+            .map(|variant| format!("The kind of `{}::{}`.", self.ident, variant));
+        Some(quote! {
+            #[doc = #doc]
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            #vis enum #kind {
+                #(#[doc = #variant_docs] #variants,)*
+            }
+
+            #[automatically_derived]
+            impl #kind {
+                /// All kinds in order of declaration.
+                pub const ALL: &'static [#kind] = &[
+                    #(#kind::#variants,)*
+                ];
+            }
+        })
+    }
+
+    fn kind_fns(&self) -> Option<TokenStream2> {
+        //! Generates `classify(mem)` and `kind(&self)`. `classify(..)` uses the same leaves as
+        //! `instruction_len(..)` and pairs the length with the kind of the leaf.
+        let kind = self.attrs.kind.as_ref()?;
+        let word = self.attrs.byte_word();
+        let matcher = self.matcher_with(
+            self.default_tree(),
+            &self.default_instructions(),
+            &word,
+            quote! { (::std::primitive::usize, #kind) },
+            &|instr| {
+                let length_decoder = instr.length_decoder(&word);
+                let variant = instr.ident();
+                quote! {
+                    (#length_decoder).map(|num_bytes| (num_bytes, #kind::#variant))
+                }
+            },
+        );
+        let kind_arms = self.instructions.iter().map(|instr| {
+            let variant = instr.ident();
+            quote! { Self::#variant{..} => #kind::#variant, }
+        });
+        Some(quote! {
+            /// Returns the number of bytes and the kind of the instruction at the start of `mem`
+            /// like `instruction_len(..)` without constructing the instruction.
+            #[allow(unused_variables)]
+            pub fn classify(mem:&[::std::primitive::u8]) -> ::std::result::Result<(::std::primitive::usize, #kind), imperative_rs::DecodeError> {
+                #matcher
+            }
+
+            /// Returns the kind of this instruction.
+            pub fn kind(&self) -> #kind {
+                match self {
+                    #(#kind_arms)*
+                }
+            }
+        })
+    }
+
+    fn bitstream_fns(&self) -> Option<TokenStream2> {
+        //! Generates `decode_bits(mem, bit_offset)` and `encode_bits(buf, bit_offset)`. The
+        //! opcode is decoded from and encoded into a window of bytes that is copied from or to
+        //! the bit stream. Lengths are counted in bits.
+        if !self.attrs.bitstream {
+            return None;
+        }
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let word = Word::bitstream();
+        let window_len = self
+            .instructions
+            .iter()
+            .map(|instr| instr.opcode().num_bits().div_ceil(8))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let matcher = self.matcher(
+            self.trees.bitstream.as_ref(),
+            &self.default_instructions(),
+            &word,
+        );
+        let encode_blocks = self
+            .instructions
+            .iter()
+            .map(|instr| instr.encoder_block(&word));
+        Some(quote! {
+            /// Decodes an instruction that starts at bit `bit_offset` of `mem`. Bits are counted
+            /// from the most significant bit of the first byte. Returns the number of bits the
+            /// instruction occupies.
+            #[allow(unused_variables)]
+            pub fn decode_bits(mem:&[::std::primitive::u8], bit_offset: ::std::primitive::usize) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                let bits_mem = mem;
+                let mut window = [0u8; #window_len];
+                let available_bits = imperative_rs::bits::read(bits_mem, bit_offset, &mut window);
+                let mem = &window[..available_bits.div_ceil(8)];
+                #matcher
+            }
+
+            /// Encodes the instruction at bit `bit_offset` of `buf`. All other bits of `buf` are
+            /// left unchanged. Returns the number of bits written.
+            #[allow(unused_variables)]
+            pub fn encode_bits(&self, buf:&mut [::std::primitive::u8], bit_offset: ::std::primitive::usize) -> ::std::result::Result<::std::primitive::usize, imperative_rs::EncodeError> {
+                let bits_buf = buf;
+                let mut window = [0u8; #window_len];
+                let buf = &mut window[..];
+                match self {
+                    #(#encode_blocks)*
+                }
+            }
+        })
+    }
+
+    fn extensions(&self) -> Vec<Ident> {
+        //! Returns the identifiers of the `EXT_..` constants for all extensions used in this
+        //! instruction set in order of appearance.
+        let mut extensions: Vec<Ident> = vec![];
+        for instr in &self.instructions {
+            if let Some(extension) = instr.attrs().extension.as_ref() {
+                let ext_ident = Self::extension_ident(extension);
+                if !extensions.contains(&ext_ident) {
+                    extensions.push(ext_ident);
+                }
+            }
+        }
+        extensions
+    }
+
+    fn extension_ident(extension: &LitStr) -> Ident {
+        Ident::new(
+            &format!("EXT_{}", extension.value().to_uppercase()),
+            extension.span(),
+        )
+    }
+
+    fn check_extensions(instructions: &Punctuated<Instruction, Token!(,)>) -> SynResult<()> {
+        //! Each extension is represented by one bit in an `ExtSet`.
+        let mut extensions: Vec<String> = vec![];
+        for instr in instructions {
+            if let Some(extension) = instr.attrs().extension.as_ref() {
+                let name = extension.value().to_uppercase();
+                if !extensions.contains(&name) {
+                    extensions.push(name);
+                }
+                if extensions.len() > 64 {
+                    return Err(Error::new(
+                        extension.span(),
+                        "Too many extensions. At most 64 different extensions are supported",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn extension_consts(&self) -> Vec<TokenStream2> {
+        self.extensions()
+            .iter()
+            .enumerate()
+            .map(|(bit, ext_ident)| {
+                let doc = format!(
+                    "The extension `{}` to be used with `decode_for(mem, enabled)`.",
+                    &ext_ident.to_string()[4..]
+                );
+                quote! {
+                    #[doc = #doc]
+                    pub const #ext_ident: imperative_rs::ExtSet = imperative_rs::ExtSet::from_bits(1 << #bit);
+                }
+            })
+            .collect()
+    }
+
+    fn encoded_len_fn(&self) -> TokenStream2 {
+        //! Generates `InstructionSet::encoded_len(&self)`, which adds up the lengths of the opcode
+        //! and the trailing fields without encoding the instruction.
+        let word = self.attrs.byte_word();
+        let arms = self
+            .instructions
+            .iter()
+            .map(|instr| instr.encoded_len_arm(&word));
+        quote! {
+            fn encoded_len(&self) -> ::std::primitive::usize {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+
+    fn to_bytes_fn(&self) -> Option<TokenStream2> {
+        //! Generates `to_bytes(&self)` together with the constant `MAX_ENCODED_LEN`. The array
+        //! returned by `to_bytes(..)` has a fixed size, so it is not generated for instruction
+        //! sets with payloads or generics.
+        let has_payloads = self.instructions.iter().any(|instr| {
+            instr
+                .trailing()
+                .iter()
+                .any(|field| matches!(field, TrailingField::Payload { .. }))
+        });
+        if has_payloads || !self.generics.params.is_empty() {
+            return None;
+        }
+        let ident = &self.ident;
+        Some(quote! {
+            /// The size of the array returned by `to_bytes(&self)`. This is `MAX_LEN` unless an
+            /// operand has no bounded length, in which case it is 0.
+            pub const MAX_ENCODED_LEN: ::std::primitive::usize = match <Self as imperative_rs::InstructionSet>::MAX_LEN {
+                ::std::option::Option::Some(len) => len,
+                ::std::option::Option::None => 0,
+            };
+
+            /// Encodes the instruction into an array on the stack that fits every instruction.
+            /// Returns the array and the number of bytes of the instruction at its start. An
+            /// instruction only doesn't fit if an operand has no bounded length, which is
+            /// reported as `EncodeError::UnexpectedEOF`.
+            pub fn to_bytes(&self) -> ::std::result::Result<([::std::primitive::u8; #ident::MAX_ENCODED_LEN], ::std::primitive::usize), imperative_rs::EncodeError> {
+                let mut buf = [0; #ident::MAX_ENCODED_LEN];
+                let num_bytes = imperative_rs::InstructionSet::encode(self, &mut buf)?;
+                ::std::result::Result::Ok((buf, num_bytes))
+            }
+        })
+    }
+
+    fn unchecked_fns(&self) -> TokenStream2 {
+        //! Generates `decode_unchecked(mem)`, `decode_padded(mem)` and the constant
+        //! `MAX_OPCODE_LEN`. They decode the same instructions as `InstructionSet::decode(..)`
+        //! but the decoder doesn't check if the opcode fits into the memory. The decoder tells
+        //! the compiler that the memory holds the longest opcode, so the bounds checks of the
+        //! leaves are left out as well.
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let word = self.attrs.byte_word();
+        let instructions = self.default_instructions();
+        let max_opcode_len = instructions
+            .iter()
+            .map(|instr| instr.opcode().num_words(&word))
+            .max()
+            .unwrap_or(0);
+        let matcher = self.unchecked_matcher_with(
+            self.default_tree(),
+            &instructions,
+            &word,
+            quote! { (::std::primitive::usize, #ident#generics) },
+            &|instr| instr.decoder(&word),
+        );
+        //an empty memory always holds the opcodes of an empty instruction set
+        let length_hint = if max_opcode_len == 0 {
+            TokenStream2::new()
+        } else {
+            quote! {
+                ::std::debug_assert!(
+                    mem.len() >= #max_opcode_len,
+                    "decode_unchecked needs at least {} bytes but got {}",
+                    #max_opcode_len,
+                    mem.len()
+                );
+                if mem.len() < #max_opcode_len {
+                    unsafe { ::std::hint::unreachable_unchecked() }
+                }
+            }
+        };
+        quote! {
+            /// The number of bytes of the longest opcode. `decode_unchecked(mem)` needs a memory
+            /// of at least this many bytes.
+            pub const MAX_OPCODE_LEN: ::std::primitive::usize = #max_opcode_len;
+
+            /// Decodes an instruction like `InstructionSet::decode(..)` without checking that
+            /// the opcode fits into `mem`. Operands, payloads and varints after the opcode are
+            /// still checked. In debug builds the length of `mem` is asserted.
+            ///
+            /// # Safety
+            /// `mem` needs to hold at least `MAX_OPCODE_LEN` bytes.
+            #[allow(unused_variables)]
+            pub unsafe fn decode_unchecked(mem:&[::std::primitive::u8]) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                #length_hint
+                #matcher
+            }
+
+            /// Decodes an instruction from a buffer that holds the longest opcode, so none of
+            /// the lengths of the opcodes need to be checked (see `decode_unchecked(mem)`). The
+            /// buffer has `MAX_OPCODE_LEN` instead of `MAX_LEN` bytes, since operands, payloads
+            /// and varints after the opcode are still checked and `MAX_LEN` doesn't exist for
+            /// instruction sets with payloads.
+            ///
+            /// Every byte of the buffer is taken to be memory. Padding a shorter memory with zeros
+            /// hides that it ends within an instruction: the padding is decoded as part of the
+            /// opcode instead of reporting `DecodeError::UnexpectedEOF`.
+            pub fn decode_padded(mem:&[::std::primitive::u8; #max_opcode_len]) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                unsafe { Self::decode_unchecked(mem) }
+            }
+        }
+    }
+
+    fn decode_for_fn(&self) -> Option<TokenStream2> {
+        //! Generates `decode_for(mem, enabled)`. It decodes the same instructions as
+        //! `InstructionSet::decode(..)` but each leaf of an instruction that belongs to an extension
+        //! first checks if the extension is enabled.
+        if self.extensions().is_empty() {
+            return None;
+        }
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let word = self.attrs.byte_word();
+        let matcher = self.matcher_with(
+            self.default_tree(),
+            &self.default_instructions(),
+            &word,
+            quote! { (::std::primitive::usize, #ident#generics) },
+            &|instr| {
+                let decoder = instr.decoder(&word);
+                match instr.attrs().extension.as_ref() {
+                    Some(extension) => {
+                        let ext_ident = Self::extension_ident(extension);
+                        quote! {
+                            if !enabled.contains(Self::#ext_ident) {
+                                Err(imperative_rs::DecodeError::UnknownOpcode)
+                            } else {
+                                #decoder
+                            }
+                        }
+                    }
+                    None => decoder,
+                }
+            },
+        );
+        Some(quote! {
+            /// Decodes an instruction like `InstructionSet::decode(..)` but treats the opcodes of
+            /// extensions that are not `enabled` as unknown opcodes.
+            pub fn decode_for(mem:&[::std::primitive::u8], enabled:imperative_rs::ExtSet) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                #matcher
+            }
+        })
+    }
+
+    fn decode_with_fn(&self) -> Option<TokenStream2> {
+        //! Generates `decode_with(mem, ctx)`. For each mode a separate decoder is built. Every mode
+        //! whose condition holds is tried in order of their first appearance until one of them
+        //! knows the opcode. If no condition holds, only the instructions without a condition are
+        //! decoded.
+        let context = self.attrs.context.as_ref()?;
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let instr_list: Vec<&Instruction> = self.instructions.iter().collect();
+        let modes = Self::modes(&instr_list);
+        let conditions = modes.iter().flatten();
+        let word = self.attrs.byte_word();
+        let matchers = modes
+            .iter()
+            .zip(&self.trees.modes)
+            .skip(1)
+            .map(|(mode, tree)| {
+                self.matcher(tree.as_ref(), &Self::in_mode(&instr_list, *mode), &word)
+            });
+        let default_matcher =
+            self.matcher(self.default_tree(), &self.default_instructions(), &word);
+        Some(quote! {
+            /// Decodes an instruction like `InstructionSet::decode(..)` but also decodes the
+            /// instructions whose `#[when = ".."]` condition holds for `ctx`. If several
+            /// conditions hold and their instructions share an opcode, the condition that appears
+            /// first in the `enum` takes precedence.
+            pub fn decode_with(mem:&[::std::primitive::u8], ctx:&#context) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
+                #(if #conditions {
+                    let res = { #matchers };
+                    match res {
+                        Err(imperative_rs::DecodeError::UnknownOpcode) => {}
+                        res => return res,
+                    }
+                })*
+                #default_matcher
+            }
+        })
+    }
+
+    fn check_cycles(instructions: &Punctuated<Instruction, Token!(,)>) -> SynResult<()> {
+        //! `cycles(&self)` is only generated if the instructions are annotated with
+        //! `#[cycles = ..]`. If one instruction is annotated, all of them need to be.
+        if instructions
+            .iter()
+            .all(|instr| instr.attrs().cycles.is_none())
+        {
+            return Ok(());
+        }
+        let mut res: SynResult<()> = Ok(());
+        for instr in instructions {
+            if instr.attrs().cycles.is_none() {
+                let err = Error::new(instr.ident().span(), format!("No cycle count defined for Instruction {}. Either all or none of the instructions need a #[cycles = ..] attribute", instr.ident()));
+                if let Err(ref mut total_error) = res {
+                    total_error.combine(err);
+                } else {
+                    res = Err(err);
+                }
+            }
+        }
+        res
+    }
+
+    fn cycles_fn(&self) -> Option<TokenStream2> {
+        let cycle_arms: Vec<TokenStream2> = self
+            .instructions
+            .iter()
+            .filter_map(|instr| instr.cycles_arm())
+            .collect();
+        if cycle_arms.is_empty() {
+            return None;
+        }
+        Some(quote! {
+            /// Returns the number of cycles this instruction takes as defined by the
+            /// `#[cycles = ..]` attribute of the variant.
+            pub fn cycles(&self) -> ::std::primitive::u32 {
+                match self {
+                    #(#cycle_arms)*
+                }
+            }
         })
     }
 }
 
 impl ToTokens for InstructionSet {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
-        use crate::matcher::MatchArm;
         let ident = &self.ident;
         let generics = &self.generics;
+        let word = self.attrs.byte_word();
 
         let encode_blocks: Vec<TokenStream2> = self
             .instructions
             .iter()
-            .map(|instr| instr.encoder_block())
+            .map(|instr| instr.encoder_block(&word))
             .collect();
 
-        let instructions: Vec<&Instruction> = self.instructions.iter().collect();
-        let matcher = MatchArm::from_list(&instructions);
+        let matcher = self.matcher(self.default_tree(), &self.default_instructions(), &word);
+        let length_fns = self.length_fns();
         let decode_fn = quote! {
             fn decode(mem:&[::std::primitive::u8]) -> ::std::result::Result<(::std::primitive::usize, #ident#generics), imperative_rs::DecodeError> {
                 #matcher
             }
         };
 
+        let encoded_len_fn = self.encoded_len_fn();
         let encode_fn = quote! {
             fn encode(&self, buf:&mut [::std::primitive::u8]) -> ::std::result::Result<::std::primitive::usize, imperative_rs::EncodeError>  {
                 match self {
@@ -74,7 +1179,36 @@ impl ToTokens for InstructionSet {
             impl#generics InstructionSet for #ident#generics {
                 #encode_fn
                 #decode_fn
+                #length_fns
+                #encoded_len_fn
             }
         });
+        tokens.extend(self.word_impl());
+        tokens.extend(self.kind_enum());
+        tokens.extend(self.profile_dependency());
+
+        let mut inherent_fns: Vec<TokenStream2> = self.extension_consts();
+        inherent_fns.push(self.unchecked_fns());
+        inherent_fns.extend(
+            vec![
+                self.to_bytes_fn(),
+                self.cycles_fn(),
+                self.decode_with_fn(),
+                self.decode_for_fn(),
+                self.bitstream_fns(),
+                self.kind_fns(),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+        if !inherent_fns.is_empty() {
+            tokens.extend(quote! {
+                #[automatically_derived]
+                #[allow(unused_qualifications)]
+                impl#generics #ident#generics {
+                    #(#inherent_fns)*
+                }
+            });
+        }
     }
 }
@@ -12,7 +12,13 @@ mod matcher;
 
 use instructionset::InstructionSet;
 
-#[proc_macro_derive(InstructionSet, attributes(opcode, variable))]
+#[proc_macro_derive(
+    InstructionSet,
+    attributes(
+        imperative, opcode, variable, endian, cycles, when, extension, operand, len, varint, slot,
+        weight
+    )
+)]
 pub fn derive_instructionset(input: TokenStream) -> TokenStream {
     let instruction_set = parse_macro_input!(input as InstructionSet);
     let tokens = quote! {#instruction_set};
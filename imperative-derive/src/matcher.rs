@@ -1,65 +1,503 @@
-use std::boxed::Box;
-use crate::instruction::Instruction;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use crate::instruction::{Instruction, Word};
+use proc_macro2::Literal;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote};
 
 /// This matcher models the structure of decoding an instruction set by implementing a binary tree.
 /// When the list only contains one instruction a `MatchArm::Leaf` is formed.
 /// If there are more than one element in the list, the most significant bit is selected (see
-/// `MatchArm::find_msb(...)`).
+/// `MatchTree::find_msb(...)`).
 /// When the bit is selected the instructions are split/forked into instructions that contain a 0
 /// in that position and those which contain a 1. Instructions that could contain both (i.e. there
 /// is variable encoded in that bit) are pushed into both lists. From these lists the zero and one
 /// arms of the fork are constructed.
-/// This struct implements `quote::ToTokens` through which it constructs the decoder for the
-/// `InstructionSet::decode(..)` method.
-pub(crate) enum MatchArm<'a> {
+/// The bit positions depend on how the opcodes are laid out in memory. So the tree is built for
+/// the `Word` the memory consists of and `MatchTree::to_tokens_with(..)` constructs the decoder for
+/// the same `Word`.
+/// With `Decoder::Table` a word is matched on multiple bits at once (see
+/// `MatchTree::find_table_mask(..)`). This forms a `MatchArm::Table` whose arms are the
+/// instructions grouped by their code in these bits. Single bit forks are only used if the bits
+/// of a word can't be split that way.
+/// Arms refer to their children by their index in `MatchTree::arms` and leaves refer to their
+/// instruction by its index in the list the tree was built from.
+pub(crate) enum MatchArm {
     Fork {
-        zero: Box<MatchArm<'a>>,
-        one: Box<MatchArm<'a>>,
+        zero: usize,
+        one: usize,
         msb: usize,
     },
+    Table {
+        word_idx: usize,
+        mask: u128,
+        arms: Vec<(u128, usize)>,
+    },
     Leaf {
-        instr: &'a Instruction,
+        instr: usize,
     },
 }
 
-impl<'a> MatchArm<'a> {
+/// The decoder backend selected by `#[imperative(decoder = "..")]`. `Tree` tests one bit at a
+/// time, `Table` dispatches on multiple bits through a `match` on the masked word.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Decoder {
+    Tree,
+    Table,
+}
+
+/// The code generation goal selected by `#[imperative(optimize = "..")]`. With `Speed` every
+/// leaf is constructed in place, even if it is reached from multiple arms. With `Size` the leaves
+/// that are reached from multiple arms are constructed once and called from all of them.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Optimize {
+    Speed,
+    Size,
+}
+
+/// The number of match arms a decoder may consist of unless
+/// `#[imperative(max_arms = ..)]` is given.
+pub(crate) const DEFAULT_MAX_ARMS: usize = 1 << 16;
+
+/// This error is returned if the decoder needs more than `max_arms` match arms. `instr` is the
+/// instruction that is part of the most arms, it is usually the one with variables in the bits
+/// that tell the other instructions apart.
+pub(crate) struct TooManyArms<'a> {
+    pub(crate) instr: &'a Instruction,
+    pub(crate) num_arms: usize,
+}
+
+/// The decoder of a list of instructions. Instructions with variables in the deciding bits are
+/// copied into both arms of a fork, so the same list of instructions can be reached on different
+/// paths. Each list is only built once and all paths share its arm. This turns the tree into a
+/// directed acyclic graph, children are always built (and stored) before their parents.
+/// The opcodes are turned into their bit patterns once and arms only store the indices of their
+/// instructions while the tree is built.
+/// The tree doesn't borrow the instructions, so it can be built once while parsing and kept until
+/// the decoders are constructed. `min_words` and `max_words` are the number of words of the
+/// shortest and longest opcode in the tree.
+pub(crate) struct MatchTree {
+    arms: Vec<MatchArm>,
+    root: usize,
+    min_words: usize,
+    max_words: usize,
+}
+
+struct TreeBuilder<'a, 'w> {
+    word: &'w Word,
+    decoder: Decoder,
+    max_arms: usize,
+    instrs: Vec<&'a Instruction>,
+    patterns: Vec<Vec<char>>,
+    weights: Vec<f32>,
+    arms: Vec<MatchArm>,
+    built: HashMap<Vec<usize>, usize>,
+    num_arms: Vec<usize>,
+}
+
+impl MatchTree {
 
-    pub(crate) fn from_list(instr_list: &Vec<&'a Instruction>) -> MatchArm<'a> {
+    pub(crate) fn from_list<'a>(instr_list: &[&'a Instruction], word: &Word, decoder: Decoder, max_arms: usize) -> Result<MatchTree, TooManyArms<'a>> {
         //! This function ceates the match arms for the given list of instructions. When given the
         //! full instruction set it will construct the full decoder for that instructionset
-        match instr_list.len()  {
+        if instr_list.is_empty() {
+            panic!("Trying to build MatchArm from empty list");
+        }
+        let mut builder = TreeBuilder {
+            word,
+            decoder,
+            max_arms,
+            instrs: instr_list.to_vec(),
+            patterns: instr_list.iter().map(|instr| instr.opcode().collision_iter(word).collect()).collect(),
+            weights: instr_list.iter().map(|instr| instr.weight() as f32).collect(),
+            arms: vec!(),
+            built: HashMap::new(),
+            num_arms: vec![0; instr_list.len()],
+        };
+        let root = builder.build((0..instr_list.len()).collect())?;
+        //every instruction is reachable from the root
+        let num_words = instr_list.iter().map(|instr| instr.opcode().num_words(word));
+        let min_words = num_words.clone().min().unwrap();
+        let max_words = num_words.max().unwrap();
+        Ok(MatchTree{ arms: builder.arms, root, min_words, max_words })
+    }
+
+    fn shared_arms(&self, optimize: Optimize) -> Vec<bool> {
+        //! Returns which arms are reached from more than one parent. Unless optimizing for size,
+        //! leaves are not shared, they are constructed in place like in a tree.
+        let mut parents = vec![0; self.arms.len()];
+        for arm in &self.arms {
+            match arm {
+                MatchArm::Fork{ zero, one, .. } => {
+                    parents[*zero] += 1;
+                    parents[*one] += 1;
+                },
+                MatchArm::Table{ arms, .. } => {
+                    for (_, arm) in arms {
+                        parents[*arm] += 1;
+                    }
+                },
+                MatchArm::Leaf{ .. } => (),
+            }
+        }
+        self.arms.iter()
+            .zip(parents)
+            .map(|(arm, parents)| parents > 1 && (optimize == Optimize::Size || !matches!(arm, MatchArm::Leaf{ .. })))
+            .collect()
+    }
+
+    pub(crate) fn to_tokens_with(&self, tokens: &mut TokenStream2, instr_list: &[&Instruction], word: &Word, optimize: Optimize, ret: &TokenStream2, leaf: &dyn Fn(&Instruction) -> TokenStream2) {
+        //! Constructs the decoder for a memory of `word`s. `instr_list` is the list the tree was
+        //! built from. The caller decides what is constructed for each leaf (e.g. an additional
+        //! check in `decode_for(..)`) and `ret` is the type the leaves evaluate to. The memory is
+        //! checked once to hold the shortest opcode, so the arms only check the length of the
+        //! memory for words beyond it.
+        let min_words = self.min_words;
+        let leaf = |instr: usize| leaf(instr_list[instr]);
+        let mut arm_tokens = TokenStream2::new();
+        self.arms_tokens(&mut arm_tokens, word, optimize, ret, &leaf, min_words);
+        tokens.extend(quote! {
+            if mem.len() < #min_words {
+                Err(imperative_rs::DecodeError::UnexpectedEOF)
+            } else {
+                #arm_tokens
+            }
+        });
+    }
+
+    pub(crate) fn to_unchecked_tokens_with(&self, tokens: &mut TokenStream2, instr_list: &[&Instruction], word: &Word, optimize: Optimize, ret: &TokenStream2, leaf: &dyn Fn(&Instruction) -> TokenStream2) {
+        //! Constructs the decoder like `MatchTree::to_tokens_with(..)` but for a memory that is
+        //! known to hold the longest opcode. None of the arms checks the length of the memory.
+        let leaf = |instr: usize| leaf(instr_list[instr]);
+        self.arms_tokens(tokens, word, optimize, ret, &leaf, self.max_words);
+    }
+
+    fn loaded_words(word: &Word, checked_words: usize) -> usize {
+        //! Returns the number of words that are loaded into one integer before the tree is
+        //! entered. These are the words the memory is known to hold, up to 128 bits.
+        checked_words.min(128 / word.bits)
+    }
+
+    fn int_bits(word: &Word, loaded_words: usize) -> usize {
+        //! Returns the number of bits of the smallest unsigned integer that holds `loaded_words`.
+        (loaded_words * word.bits).next_power_of_two().max(8)
+    }
+
+    fn word_load(&self, word: &Word, checked_words: usize) -> TokenStream2 {
+        //! Loads the words of the memory that are known to be there into `imperative_word`, so
+        //! the forks and tables test their bits without indexing into the memory. The first word
+        //! is loaded into the most significant bits like the bits are numbered in the tree. Bits
+        //! of a word above its width are masked out. Nothing is loaded if no arm tests these
+        //! words.
+        let loaded_words = Self::loaded_words(word, checked_words);
+        let uses_word = self.arms.iter().any(|arm| match arm {
+            MatchArm::Fork{ msb, .. } => msb / word.bits < loaded_words,
+            MatchArm::Table{ word_idx, .. } => *word_idx < loaded_words,
+            MatchArm::Leaf{ .. } => false,
+        });
+        if !uses_word {
+            return TokenStream2::new();
+        }
+        let int_bits = Self::int_bits(word, loaded_words);
+        let int_ty = format_ident!("u{}", int_bits);
+        let load = if word.is_byte() {
+            let bytes = (0..int_bits / 8).map(|idx| if idx < loaded_words {
+                quote! { mem[#idx] }
+            } else {
+                quote! { 0 }
+            });
+            quote! { ::std::primitive::#int_ty::from_be_bytes([#(#bytes),*]) }
+        } else {
+            let word_mask = Literal::u128_unsuffixed((1 << word.bits) - 1);
+            let words = (0..loaded_words).map(|idx| {
+                let shift = int_bits - (idx + 1) * word.bits;
+                quote! { ((mem[#idx] as ::std::primitive::#int_ty) & #word_mask) << #shift }
+            });
+            quote! { #(#words)|* }
+        };
+        quote! {
+            let imperative_word = #load;
+        }
+    }
+
+    fn arms_tokens(&self, tokens: &mut TokenStream2, word: &Word, optimize: Optimize, ret: &TokenStream2, leaf: &dyn Fn(usize) -> TokenStream2, checked_words: usize) {
+        //! Constructs the arms of the tree for a memory that is known to hold `checked_words`.
+        //! Shared arms are constructed once as closures that are called from all their parents.
+        //! Children are stored before their parents, so every closure is defined before it is
+        //! called. The closures capture the loaded words of the memory.
+        let shared = self.shared_arms(optimize);
+        let word_ty = &word.ty;
+        let word_load = self.word_load(word, checked_words);
+        let closures = (0..self.arms.len()).filter(|idx| shared[*idx]).map(|idx| {
+            let closure = Self::closure_ident(idx);
+            let mut arm_tokens = TokenStream2::new();
+            self.arm_tokens(&mut arm_tokens, idx, &shared, word, leaf, checked_words);
+            quote! {
+                let #closure = |mem: &[#word_ty]| -> ::std::result::Result<#ret, imperative_rs::DecodeError> {
+                    #arm_tokens
+                };
+            }
+        });
+        let mut root_tokens = TokenStream2::new();
+        self.child_tokens(&mut root_tokens, self.root, &shared, word, leaf, checked_words);
+        tokens.extend(quote! {
+            #word_load
+            #(#closures)*
+            #root_tokens
+        });
+    }
+
+    fn closure_ident(idx: usize) -> proc_macro2::Ident {
+        format_ident!("imperative_arm_{}", idx)
+    }
+
+    fn child_tokens(&self, tokens: &mut TokenStream2, idx: usize, shared: &[bool], word: &Word, leaf: &dyn Fn(usize) -> TokenStream2, checked_words: usize) {
+        //! Constructs the decoder of a child. Shared children are called, all others are
+        //! constructed in place.
+        if shared[idx] {
+            let closure = Self::closure_ident(idx);
+            tokens.extend(quote! { #closure(mem) });
+        } else {
+            self.arm_tokens(tokens, idx, shared, word, leaf, checked_words);
+        }
+    }
+
+    fn arm_tokens(&self, tokens: &mut TokenStream2, idx: usize, shared: &[bool], word: &Word, leaf: &dyn Fn(usize) -> TokenStream2, checked_words: usize) {
+        //! Constructs the decoder of this arm. The memory is known to hold `checked_words`. Words
+        //! that were loaded by `word_load(..)` are tested in `imperative_word`, only the words
+        //! beyond them are read from the memory.
+        let loaded_words = Self::loaded_words(word, checked_words);
+        let int_bits = Self::int_bits(word, loaded_words);
+        match &self.arms[idx] {
+            MatchArm::Fork{ zero, one, msb } => {
+                let (byte_idx, bit_idx) = (msb/word.bits, msb%word.bits);
+                let test = if byte_idx < loaded_words {
+                    let bit_mask = Literal::u128_unsuffixed(1 << (int_bits - 1 - msb));
+                    quote! { imperative_word & #bit_mask }
+                } else {
+                    let bit_mask = Literal::u128_unsuffixed(1 << (word.bits - 1 - bit_idx));
+                    quote! { mem[#byte_idx] & #bit_mask }
+                };
+                let mut zero_tokens = TokenStream2::new();
+                let mut one_tokens = TokenStream2::new();
+                self.child_tokens(&mut zero_tokens, *zero, shared, word, leaf, checked_words);
+                self.child_tokens(&mut one_tokens, *one, shared, word, leaf, checked_words);
+                let fork = quote! {
+                    if #test == 0 {
+                        #zero_tokens
+                    } else  {
+                        #one_tokens
+                    }
+                };
+                tokens.extend(if byte_idx < checked_words {
+                    fork
+                } else {
+                    quote! {
+                        if #byte_idx >= mem.len() {
+                            Err(imperative_rs::DecodeError::UnexpectedEOF)
+                        } else #fork
+                    }
+                });
+            },
+            MatchArm::Table{ word_idx, mask, arms } => {
+                let codes = arms.iter().map(|(code, _)| Literal::u128_unsuffixed(*code));
+                let arm_tokens = arms.iter().map(|(_, arm)| {
+                    let mut arm_tokens = TokenStream2::new();
+                    self.child_tokens(&mut arm_tokens, *arm, shared, word, leaf, checked_words);
+                    arm_tokens
+                });
+                //the match is only exhaustive without a default arm if every code has an arm
+                let default = if mask.count_ones() as usize == word.bits && arms.len() as u128 == 1 << word.bits {
+                    TokenStream2::new()
+                } else {
+                    quote! { _ => Err(imperative_rs::DecodeError::UnknownOpcode), }
+                };
+                let mask = Literal::u128_unsuffixed(*mask);
+                let word_ty = &word.ty;
+                let masked = if *word_idx < loaded_words {
+                    let shift = int_bits - (word_idx + 1) * word.bits;
+                    quote! { (imperative_word >> #shift) as #word_ty & #mask }
+                } else {
+                    quote! { mem[#word_idx] & #mask }
+                };
+                let table = quote! {
+                    match #masked {
+                        #(#codes => { #arm_tokens },)*
+                        #default
+                    }
+                };
+                tokens.extend(if *word_idx < checked_words {
+                    table
+                } else {
+                    quote! {
+                        if #word_idx >= mem.len() {
+                            Err(imperative_rs::DecodeError::UnexpectedEOF)
+                        } else {
+                            #table
+                        }
+                    }
+                });
+            },
+            MatchArm::Leaf{ instr } => {
+                tokens.extend(leaf(*instr));
+            },
+        }
+    }
+}
+
+impl<'a, 'w> TreeBuilder<'a, 'w> {
+
+    fn build(&mut self, instr_list: Vec<usize>) -> Result<usize, TooManyArms<'a>> {
+        //! Builds the arm for the given list of instructions (given by their index) and returns
+        //! its index. If the list was already built, the existing arm is returned.
+        if let Some(idx) = self.built.get(&instr_list) {
+            return Ok(*idx);
+        }
+        let arm = match instr_list.len()  {
             0 => panic!("Trying to build MatchArm from empty list"),
             1 => {
                 MatchArm::Leaf{instr:instr_list[0]}
             },
             _ => {
-                let msb = Self::find_msb(&instr_list); 
-                let (zero_instrs, one_instrs) = Self::fork_instructions(&instr_list, msb);
-                let (zero_arm, one_arm) = (Self::from_list(&zero_instrs), Self::from_list(&one_instrs));
-                MatchArm::Fork{zero:Box::new(zero_arm), one:Box::new(one_arm), msb}
+                let msb = self.find_msb(&instr_list);
+                let mut table = None;
+                if self.decoder == Decoder::Table {
+                    let word_idx = msb / self.word.bits;
+                    let mask = self.find_table_mask(&instr_list, word_idx);
+                    if mask != 0 {
+                        table = Some(self.table(&instr_list, word_idx, mask)?);
+                    }
+                }
+                match table {
+                    Some(table) => table,
+                    None => {
+                        let (zero_instrs, one_instrs) = self.fork_instructions(&instr_list, msb);
+                        let zero = self.build(zero_instrs)?;
+                        let one = self.build(one_instrs)?;
+                        MatchArm::Fork{zero, one, msb}
+                    },
+                }
             },
+        };
+        for instr in &instr_list {
+            self.num_arms[*instr] += 1;
+        }
+        self.arms.push(arm);
+        if self.arms.len() > self.max_arms {
+            //report the instruction that is copied into the most arms
+            let (instr, num_arms) = self.num_arms.iter().enumerate().max_by_key(|(_, num_arms)| **num_arms).unwrap();
+            return Err(TooManyArms{ instr: self.instrs[instr], num_arms: *num_arms });
+        }
+        let idx = self.arms.len() - 1;
+        self.built.insert(instr_list, idx);
+        Ok(idx)
+    }
+
+    fn word_bits<'p>(&'p self, instr: usize, word_idx: usize) -> impl Iterator<Item = char> + 'p {
+        self.patterns[instr].iter().skip(word_idx * self.word.bits).take(self.word.bits).copied()
+    }
+
+    fn find_table_mask(&self, instr_list: &[usize], word_idx: usize) -> u128 {
+        //! Returns the mask of the bits in the word at `word_idx` that the table matches on.
+        //! Preferably these are the bits that are constant (i.e. '0' or '1') in all instructions
+        //! of the list and that are not the same in all of them. Only these bits sort each
+        //! instruction into exactly one arm of a table.
+        //! If there are no such bits (e.g. a field that holds a register in some instructions
+        //! and selects the operation in others) the bits are added by their score (see
+        //! `TreeBuilder::bit_scores(..)`) as long as the instructions that are variable in them
+        //! are copied into at most twice as many arms as there are instructions.
+        let words: Vec<Vec<char>> = instr_list
+            .iter()
+            .map(|instr| self.word_bits(*instr, word_idx).collect())
+            .collect();
+        let mut mask = 0;
+        for bit_idx in 0..self.word.bits {
+            let constant = words.iter().all(|w| w[bit_idx] != '*');
+            let differs = words.iter().any(|w| w[bit_idx] != words[0][bit_idx]);
+            if constant && differs {
+                mask |= 1 << (self.word.bits - 1 - bit_idx);
+            }
+        }
+        if mask != 0 {
+            return mask;
+        }
+        let scores = self.bit_scores(instr_list, &self.weights);
+        let mut bits: Vec<usize> = (0..self.word.bits)
+            .filter(|bit_idx| scores[word_idx * self.word.bits + bit_idx] > 0.0)
+            .collect();
+        bits.sort_by(|lhs, rhs| scores[word_idx * self.word.bits + rhs].partial_cmp(&scores[word_idx * self.word.bits + lhs]).unwrap());
+        for bit_idx in bits {
+            let candidate = mask | 1 << (self.word.bits - 1 - bit_idx);
+            let num_entries: usize = words.iter().map(|w| 1 << Self::variable_bits(w, candidate, self.word.bits).count_ones()).sum();
+            if num_entries <= 2 * instr_list.len() {
+                mask = candidate;
+            }
+        }
+        //a single bit is a fork, and every arm needs to hold fewer instructions than the table
+
+        if mask.count_ones() < 2 || self.table_groups(instr_list, word_idx, mask).values().any(|group| group.len() == instr_list.len()) {
+            0
+        } else {
+            mask
         }
     }
 
-    fn fork_instructions(instr_list:&Vec<&'a Instruction>, msb:usize) -> (Vec<&'a Instruction>, Vec<&'a Instruction>) {
+    fn variable_bits(bits: &[char], mask: u128, word_bits: usize) -> u128 {
+        //! Returns the bits of the mask that are variable in `bits`.
+        bits.iter()
+            .enumerate()
+            .filter(|(_, c)| **c == '*')
+            .fold(0, |variable, (bit_idx, _)| variable | 1 << (word_bits - 1 - bit_idx)) & mask
+    }
+
+    fn table_groups(&self, instr_list: &[usize], word_idx: usize, mask: u128) -> BTreeMap<u128, Vec<usize>> {
+        //! Groups the instructions by their code in the bits of `mask`. Instructions that are
+        //! variable in some bits of the mask are put into the groups of all codes they can have.
+        let mut groups: BTreeMap<u128, Vec<usize>> = BTreeMap::new();
+        for instr in instr_list {
+            let bits: Vec<char> = self.word_bits(*instr, word_idx).collect();
+            let code = bits.iter().fold(0, |code, c| (code << 1) | if *c == '1' { 1 } else { 0 }) & mask;
+            let variable = Self::variable_bits(&bits, mask, self.word.bits);
+            //iterate over all subsets of the variable bits
+            let mut subset: u128 = 0;
+            loop {
+                groups.entry(code | subset).or_default().push(*instr);
+                if subset == variable {
+                    break;
+                }
+                subset = (subset | !variable).wrapping_add(1) & variable;
+            }
+        }
+        groups
+    }
+
+    fn table(&mut self, instr_list: &[usize], word_idx: usize, mask: u128) -> Result<MatchArm, TooManyArms<'a>> {
+        //! Builds the arms of the table from the groups of `TreeBuilder::table_groups(..)`. The
+        //! bits of the mask are the same for all instructions in a group, so the arms are split on
+        //! other bits.
+        let mut arms = vec!();
+        for (code, instrs) in self.table_groups(instr_list, word_idx, mask) {
+            arms.push((code, self.build(instrs)?));
+        }
+        Ok(MatchArm::Table{word_idx, mask, arms})
+    }
+
+    fn fork_instructions(&self, instr_list:&[usize], msb:usize) -> (Vec<usize>, Vec<usize>) {
         //! Forks a list of instructions into two lists depending on the defined most significant
         //! bit. For each instruction the function checks if the bit in it's opcode is constant (i.e
         //! '0' or '1') or contains a variables (i.e. '*'). If it is constant it's sorted into
         //! corresponding branch. If it's variable (meaning the bit could be either 1 or 0) it is
         //! put into both branches
-        let mut ones: Vec<&Instruction> = vec!();
-        let mut zeros: Vec<&Instruction> = vec!();
+        let mut ones: Vec<usize> = vec!();
+        let mut zeros: Vec<usize> = vec!();
 
         for instr in instr_list {
-            let bit = instr.opcode().collision_iter().nth(msb).unwrap();
-            match bit {
-                '0' => zeros.push(instr),
-                '1' => ones.push(instr),
+            match self.patterns[*instr][msb] {
+                '0' => zeros.push(*instr),
+                '1' => ones.push(*instr),
                 '*' => {
-                    zeros.push(instr);
-                    ones.push(instr);
+                    zeros.push(*instr);
+                    ones.push(*instr);
                 }
                 _ => panic!("Encountered unexpected symbol while building match arms. This is an internal error. Please consider posting on github.com"),
 
@@ -69,85 +507,71 @@ impl<'a> MatchArm<'a> {
         (zeros, ones)
     }
 
-    fn find_msb(instr_list: &Vec<&Instruction>) -> usize {
-        //! This function calculates the most significant bit in terms of information content.
-        //! It does so by counting the instructions that contain a '0', '1' and '*' ('*' meaning
-        //! that there is a variable encoded in this bit). Then it calculates the information
-        //! content of this bit for all instructions where it is constant (i.e. where it is not
-        //! '*') and weights it by the proportion of instructions that are variable in this bit.
-        //! A bit scores the highest of 1 when half the list has a '0' in this position and the other
-        //! half has a '1'. If all instructions are the same or variable (i.e. '*'/there is a
-        //! variable decoded in it) in a bit the bit is useless and scores 0.
+    fn find_msb(&self, instr_list: &[usize]) -> usize {
+        //! This function calculates the most significant bit in terms of information content
+        //! (see `TreeBuilder::bit_scores(..)`).
         //! In simple terms this function trys to split the list into two, trying to minimize the
         //! amount of instructions that need to be duplicated into both lists (because they contain
         //! a variable in the deciding bit) while keeping both lists the same length.
-        let opcodes: Vec<Vec<char>> = instr_list
-            .iter()
-            .map(|instr| instr.opcode().collision_iter().collect())
-            .collect();
-        let num_opcodes:f32 = opcodes.len() as f32;
-        let min_len = opcodes.iter().map(|op| op.len()).min().unwrap();
-        let mut zeros: Vec<f32> = Vec::with_capacity(min_len); //for each bit position count number of instructions with zero in that position
-        let mut ones: Vec<f32> = Vec::with_capacity(min_len); //for each bit position count number of instructions with one in that position
-        let mut vars: Vec<f32> = Vec::with_capacity(min_len); //for each bit position count number of instructions with variable in that position
+        //! Each instruction is counted with its weight (see `#[weight = ..]`), so the lists are
+        //! balanced by execution frequency and the heaviest instructions end up close to the root.
+        //! If the weights can't tell the remaining instructions apart (e.g. all of them weigh 0)
+        //! every instruction is counted once.
+        match Self::best_bit(&self.bit_scores(instr_list, &self.weights)) {
+            Some(msb) => msb,
+            None => Self::best_bit(&self.bit_scores(instr_list, &vec![1.0; self.weights.len()])).unwrap_or(0),
+        }
+    }
 
-        for _ in 0..min_len {
-            zeros.push(0.0);
-            ones.push(0.0);
-            vars.push(0.0);
+    fn best_bit(scores: &[f32]) -> Option<usize> {
+        //! Returns the first bit with the highest score or `None` if no bit scores above 0.
+        let mut max_score_idx = None;
+        let mut max_score = 0.0;
+        for (idx, score) in scores.iter().enumerate() {
+            if *score > max_score {
+                max_score_idx = Some(idx);
+                max_score = *score;
+            }
         }
+        max_score_idx
+    }
 
-        for opcode in &opcodes {
-            for (bit_idx, c) in opcode.iter().take(min_len).enumerate() {
+    fn bit_scores(&self, instr_list: &[usize], weights: &[f32]) -> Vec<f32> {
+        //! This function scores each bit by its information content. It does so by counting the
+        //! instructions that contain a '0', '1' and '*' ('*' meaning that there is a variable
+        //! encoded in this bit). Then it calculates the information content of this bit for all
+        //! instructions where it is constant (i.e. where it is not '*') and weights it by the
+        //! proportion of instructions that are variable in this bit.
+        //! A bit scores the highest of 1 when half the list has a '0' in this position and the other
+        //! half has a '1'. If all instructions are the same or variable (i.e. '*'/there is a
+        //! variable decoded in it) in a bit the bit is useless and scores 0.
+        //! Each instruction is counted with the weight at its index.
+        let num_opcodes:f32 = instr_list.iter().map(|instr| weights[*instr]).sum();
+        let min_len = instr_list.iter().map(|instr| self.patterns[*instr].len()).min().unwrap();
+        if num_opcodes <= 0.0 {
+            return vec![0.0; min_len];
+        }
+        let mut zeros: Vec<f32> = vec![0.0; min_len]; //for each bit position the weight of instructions with zero in that position
+        let mut ones: Vec<f32> = vec![0.0; min_len]; //for each bit position the weight of instructions with one in that position
+        let mut vars: Vec<f32> = vec![0.0; min_len]; //for each bit position the weight of instructions with variable in that position
+
+        for instr in instr_list {
+            let weight = weights[*instr];
+            for (bit_idx, c) in self.patterns[*instr].iter().take(min_len).enumerate() {
                 match *c {
-                    '0' => zeros[bit_idx] += 1.0,
-                    '1' => ones[bit_idx] += 1.0, 
-                    '*' => vars[bit_idx] += 1.0,
+                    '0' => zeros[bit_idx] += weight,
+                    '1' => ones[bit_idx] += weight, 
+                    '*' => vars[bit_idx] += weight,
                     _ => panic!("Encountered unexpected symbol while building match arms. This is an internal error. Please consider posting on github.com"),
                 }
             }
         }
 
-        let mut max_score_idx = 0;
-        let mut max_score = 0.0;
-        for (idx, (o, (z, v))) in zeros.iter().zip(ones.iter().zip(vars.iter())).enumerate() {
+        zeros.iter().zip(ones.iter().zip(vars.iter())).map(|(z, (o, v))| {
             let (o, z, v) = (o/num_opcodes, z/num_opcodes, v/num_opcodes);
             let ones_score = if o > 0.0 {o*o.log2() } else { 0.0 };
             let zeros_score = if z > 0.0 {z*z.log2() } else { 0.0 };
-            let score = (1.0 - v) * (-ones_score - zeros_score);
-            if score > max_score {
-                max_score_idx = idx;
-                max_score = score;
-            }
-        }
-        max_score_idx
-    }
-}
-
-impl<'a> ToTokens for MatchArm<'a> {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
-        match self {
-            MatchArm::Fork{ zero, one, msb } => {
-                let (byte_idx, bit_idx) = (msb/8, msb%8);
-                let bit_mask:u8 = 1 << 7 - bit_idx;
-                tokens.extend(quote! {
-                    if #byte_idx >= mem.len() {
-                        Err(imperative_rs::DecodeError::UnexpectedEOF)
-                    } else if mem[#byte_idx] & #bit_mask == 0 {
-                        #zero           
-                    } else  {
-                        #one
-                    }
-                });
-            },
-            MatchArm::Leaf{ instr } => {
-                tokens.extend(instr.decoder_block());
-                tokens.extend(quote!{
-                    else {
-                        Err(imperative_rs::DecodeError::UnknownOpcode)
-                    }
-                });
-            },
-        }
+            (1.0 - v) * (-ones_score - zeros_score)
+        }).collect()
     }
 }
@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+#[imperative(bitstream)]
+enum Is{
+    #[opcode = "0b101"]
+    A,
+    #[opcode = "0b10101010"]
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use imperative_rs::InstructionSet;
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[ opcode = "0x00" ]
+    A,
+    #[ opcode = "0x02" ]
+    B,
+    #[ opcode = "0x04" ]
+    C,
+    #[ opcode = "0x06" ]
+    D,
+    #[ opcode = "0x08" ]
+    E,
+    #[ opcode = "0x0a" ]
+    F,
+    #[ opcode = "0x0c" ]
+    G,
+    #[ opcode = "0x0e" ]
+    H,
+    #[ opcode = "0x1v" ]
+    I{v:u8},
+    #[ opcode = "0x2v" ]
+    J{v:u8},
+    #[ opcode = "0x15" ]
+    K,
+    #[ opcode = "0x0v" ]
+    L{v:u8},
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use imperative_rs::InstructionSet;
+
+enum Mode {
+    Arm,
+    Thumb,
+}
+
+#[derive(InstructionSet)]
+#[imperative(context = "Mode")]
+enum Instructionset {
+    #[opcode = "0x0*"]
+    A,
+    #[opcode = "0x1*"]
+    #[when = "matches!(ctx, Mode::Arm)"]
+    B,
+    #[opcode = "0x1*"]
+    #[when = "matches!(ctx, Mode::Thumb)"]
+    C,
+    #[opcode = "0x0f"]
+    #[when = "matches!(ctx, Mode::Thumb)"]
+    D,
+}
+
+fn main() {}
@@ -0,0 +1,48 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Cycles {
+    #[opcode = "0x00"]
+    #[cycles = 2]
+    Nop,
+    #[opcode = "0x1x"]
+    #[cycles = "4 + x"]
+    Load { x: u8 },
+    #[opcode = "0b0010000p_mmmmmmmm"]
+    #[cycles = "4 + page_cross as u32"]
+    Jump {
+        #[variable = "p"]
+        page_cross: bool,
+        #[variable = "m"]
+        addr: u8,
+    },
+}
+
+#[test]
+fn cycle_counts() {
+    assert_eq!(
+        2,
+        Cycles::Nop.cycles(),
+        "Wrong cycle count for constant cycles"
+    );
+    assert_eq!(
+        7,
+        Cycles::Load { x: 3 }.cycles(),
+        "Wrong cycle count for cycles depending on a variable"
+    );
+    assert_eq!(
+        4,
+        Cycles::Jump {
+            page_cross: false,
+            addr: 0xab
+        }
+        .cycles(),
+        "Wrong cycle count for cycles depending on a bool"
+    );
+    let (_, instr) = Cycles::decode(&[0x21, 0xab]).unwrap();
+    assert_eq!(
+        5,
+        instr.cycles(),
+        "Wrong cycle count for decoded instruction"
+    );
+}
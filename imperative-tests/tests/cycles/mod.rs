@@ -0,0 +1 @@
+mod cycle_counts;
@@ -0,0 +1,152 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Reg {
+    #[opcode = "0b0xx"]
+    Low { x: u8 },
+    #[opcode = "0b1xxxx"]
+    High { x: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Packed {
+    #[opcode = "0b000"]
+    Nop,
+    #[opcode = "0b001xxxxx"]
+    Load { x: u8 },
+    #[opcode = "0b01xxxxxxxxxx"]
+    Jump { x: u16 },
+    #[opcode = "0b10"]
+    Mov {
+        #[operand]
+        dst: Reg,
+        #[operand]
+        src: Reg,
+    },
+    #[opcode = "0b110"]
+    Data {
+        #[varint = "uleb128"]
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0b111"]
+    Halt,
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Blob {
+    #[opcode = "0x01"]
+    Data {
+        #[varint = "uleb128"]
+        n: u64,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn decoding_bitstream() {
+    let mem = [
+        0b1110_1101,
+        0b0100_1010,
+        0b0000_1101,
+        0b1010_0101,
+        0b1001_1110,
+    ];
+    assert_eq!(
+        Ok((3, Packed::Halt)),
+        Packed::decode_bits(&mem, 0),
+        "Failed to decode instruction at the start of the stream"
+    );
+    assert_eq!(
+        Ok((12, Packed::Jump { x: 0x2a5 })),
+        Packed::decode_bits(&mem, 3),
+        "Failed to decode instruction spanning two bytes"
+    );
+    assert_eq!(
+        Ok((3, Packed::Nop)),
+        Packed::decode_bits(&mem, 15),
+        "Failed to decode instruction starting in the last bit of a byte"
+    );
+    assert_eq!(
+        Ok((8, Packed::Load { x: 0b10110 })),
+        Packed::decode_bits(&mem, 18),
+        "Failed to decode unaligned instruction with the length of a byte"
+    );
+    assert_eq!(
+        Ok((
+            10,
+            Packed::Mov {
+                dst: Reg::Low { x: 2 },
+                src: Reg::High { x: 9 }
+            }
+        )),
+        Packed::decode_bits(&mem, 26),
+        "Failed to decode unaligned operands"
+    );
+    assert_eq!(
+        Ok((3, Packed::Halt)),
+        Packed::decode_bits(&mem, 36),
+        "Failed to decode instruction at the end of the stream"
+    );
+}
+
+#[test]
+fn decoding_bitstream_payloads() {
+    let mem = [
+        0b1110_0011,
+        0b0000_0001,
+        0b0101_0101,
+        0b1110_0110,
+        0b1111_0000,
+    ];
+    assert_eq!(
+        Ok((
+            27,
+            Packed::Data {
+                n: 2,
+                data: vec![0xab, 0xcd]
+            }
+        )),
+        Packed::decode_bits(&mem, 6),
+        "Failed to decode unaligned varint and payload"
+    );
+    assert_eq!(
+        Ok((3, Packed::Halt)),
+        Packed::decode_bits(&mem, 33),
+        "Failed to decode instruction following a payload"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Packed::decode_bits(&mem[..3], 6),
+        "Truncated payload should be reported"
+    );
+    let mut hostile = vec![0x01];
+    hostile.extend(&[0xff; 9]);
+    hostile.push(0x01);
+    hostile.extend(&[0xab; 4]);
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Blob::decode_bits(&hostile, 0),
+        "Payload length close to usize::MAX should be reported"
+    );
+}
+
+#[test]
+fn decoding_bitstream_matches_bytes() {
+    let mem = [0b0010_1101, 0b0110_1010, 0b0101_0000];
+    assert_eq!(
+        Packed::decode(&mem).map(|(num_bytes, instr)| (8 * num_bytes, instr)),
+        Packed::decode_bits(&mem, 0),
+        "Byte aligned instructions should decode like InstructionSet::decode"
+    );
+    assert_eq!(
+        Packed::decode(&mem[1..]),
+        Packed::decode_bits(&mem, 8).map(|(num_bits, instr)| (num_bits.div_ceil(8), instr)),
+        "Byte aligned instructions should decode like InstructionSet::decode"
+    );
+}
@@ -0,0 +1,155 @@
+use imperative_rs::{DecodeError, InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Alu {
+    #[opcode = "0b0xxxyyy"]
+    Add { x: u8, y: u8 },
+    #[opcode = "0b1xxxxxx"]
+    Li { x: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Mem {
+    #[opcode = "0b00xxxxx"]
+    Load { x: u8 },
+    #[opcode = "0b01xxxxx"]
+    Store { x: u8 },
+    #[opcode = "0b1******"]
+    Nop,
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Bundle {
+    #[opcode = "0b00sssssssttttttt"]
+    AluAlu {
+        #[slot]
+        s: Alu,
+        #[slot]
+        t: Alu,
+    },
+    #[opcode = "0b01sssssssttttttt"]
+    AluMem {
+        #[slot]
+        s: Alu,
+        #[slot]
+        t: Mem,
+    },
+    #[opcode = "0b10sssssssttttttt"]
+    MemMem {
+        #[slot]
+        s: Mem,
+        #[slot]
+        t: Mem,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Op {
+    #[opcode = "0b00xxxxxxxxxxxx"]
+    Imm { x: u16 },
+    #[opcode = "0b01xxxxxxyyyyyy"]
+    Add { x: u8, y: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Packet {
+    #[opcode = "0bsssssss_11_sssssss"]
+    One {
+        #[slot]
+        s: Op,
+    },
+    #[opcode = "0bsssssss_01_sssssss_ttttttt_11_ttttttt"]
+    Two {
+        #[slot]
+        s: Op,
+        #[slot]
+        t: Op,
+    },
+}
+
+#[test]
+fn decoding_bundles() {
+    assert_eq!(
+        Ok((
+            2,
+            Bundle::AluMem {
+                s: Alu::Add { x: 3, y: 5 },
+                t: Mem::Store { x: 9 }
+            }
+        )),
+        Bundle::decode(&[0x4e, 0xa9]),
+        "Failed to decode slots of different types"
+    );
+    assert_eq!(
+        Ok((
+            2,
+            Bundle::AluAlu {
+                s: Alu::Li { x: 0x2a },
+                t: Alu::Add { x: 7, y: 0 }
+            }
+        )),
+        Bundle::decode(&[0x35, 0x38]),
+        "Failed to decode slots of the same type"
+    );
+    assert_eq!(
+        Ok((
+            2,
+            Bundle::MemMem {
+                s: Mem::Nop,
+                t: Mem::Load { x: 31 }
+            }
+        )),
+        Bundle::decode(&[0xaa, 0x9f]),
+        "Failed to decode slot with ignored bits"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Bundle::decode(&[0xc0, 0x00]),
+        "Unknown template should be reported"
+    );
+}
+
+#[test]
+fn decoding_packets() {
+    assert_eq!(
+        Ok((
+            4,
+            Packet::Two {
+                s: Op::Add { x: 5, y: 9 },
+                t: Op::Imm { x: 0xabc }
+            }
+        )),
+        Packet::decode(&[0x44, 0xc9, 0x2b, 0xbc]),
+        "Failed to decode slots split by parse bits"
+    );
+    assert_eq!(
+        Ok((
+            2,
+            Packet::One {
+                s: Op::Imm { x: 1 }
+            }
+        )),
+        Packet::decode(&[0x01, 0x81]),
+        "Failed to decode packet with one slot"
+    );
+    assert_eq!(
+        Ok((
+            2,
+            Packet::Two {
+                s: Op::Add { x: 5, y: 9 },
+                t: Op::Imm { x: 0xabc }
+            }
+        )),
+        Packet::decode_words(&[0x44c9u16, 0x2bbc]),
+        "Failed to decode slots from words"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Packet::decode(&[0x81, 0x81]),
+        "Unknown opcode in a slot should be reported"
+    );
+}
@@ -0,0 +1,175 @@
+use imperative_rs::{bus::Bus, DecodeError, InstructionSet};
+use std::cell::RefCell;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Isa {
+    #[opcode = "0xea"]
+    Nop,
+    #[opcode = "0xa9_xx"]
+    Lda { x: u8 },
+    #[opcode = "0x4c_xx_xx"]
+    Jmp { x: u16 },
+    #[opcode = "0x02_nn"]
+    Data {
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0x03"]
+    Blob {
+        #[varint = "uleb128"]
+        n: u64,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+/// 16 KiB of fixed ROM followed by a switchable 16 KiB bank. Every read is logged.
+struct Banked {
+    fixed: Vec<u8>,
+    banks: Vec<Vec<u8>>,
+    bank: usize,
+    reads: RefCell<Vec<u16>>,
+}
+
+impl Bus<u16> for Banked {
+    fn read(&self, addr: u16) -> Option<u8> {
+        self.reads.borrow_mut().push(addr);
+        let addr = addr as usize;
+        if addr < 0x4000 {
+            Some(self.fixed[addr])
+        } else {
+            Some(self.banks[self.bank][(addr - 0x4000) % 0x4000])
+        }
+    }
+}
+
+fn banked() -> Banked {
+    let mut fixed = vec![0xea; 0x4000];
+    fixed[0x3ffe] = 0x4c;
+    fixed[0x3fff] = 0x34;
+    let mut first = vec![0xea; 0x4000];
+    first[0] = 0x12;
+    let mut second = vec![0xea; 0x4000];
+    second[0] = 0x56;
+    Banked {
+        fixed,
+        banks: vec![first, second],
+        bank: 0,
+        reads: RefCell::new(vec![]),
+    }
+}
+
+#[test]
+fn decoding_across_banks() {
+    let mut bus = banked();
+    assert_eq!(
+        Ok((3, Isa::Jmp { x: 0x3412 })),
+        Isa::decode_at(&bus, 0x3ffeu16),
+        "Failed to decode across bank boundary"
+    );
+    bus.bank = 1;
+    assert_eq!(
+        Ok((3, Isa::Jmp { x: 0x3456 })),
+        Isa::decode_at(&bus, 0x3ffeu16),
+        "Failed to decode from switched bank"
+    );
+}
+
+#[test]
+fn only_instruction_bytes_are_read() {
+    let bus = banked();
+    assert_eq!(Ok((1, Isa::Nop)), Isa::decode_at(&bus, 0x0000u16));
+    assert_eq!(vec![0x0000], *bus.reads.borrow());
+    bus.reads.borrow_mut().clear();
+    Isa::decode_at(&bus, 0x3ffeu16).unwrap();
+    assert_eq!(vec![0x3ffe, 0x3fff, 0x4000], *bus.reads.borrow());
+}
+
+#[test]
+fn decoding_wraps_around() {
+    let mut mem = vec![0u8; 0x10000];
+    mem[0xffff] = 0x4c;
+    mem[0x0000] = 0xab;
+    mem[0x0001] = 0xcd;
+    let bus = |addr: u16| Some(mem[addr as usize]);
+    assert_eq!(
+        Ok((3, Isa::Jmp { x: 0xabcd })),
+        Isa::decode_at(&bus, 0xffffu16),
+        "Failed to wrap around at the end of the address space"
+    );
+}
+
+#[test]
+fn decoding_from_slices() {
+    let mut mem = vec![0x02, 40];
+    mem.extend(0..40);
+    mem.push(0xa9);
+    mem.push(0x07);
+    assert_eq!(
+        Ok((
+            42,
+            Isa::Data {
+                n: 40,
+                data: (0..40).collect()
+            }
+        )),
+        Isa::decode_at(&mem[..], 0usize),
+        "Failed to decode instruction longer than the stack buffer"
+    );
+    assert_eq!(
+        Ok((2, Isa::Lda { x: 0x07 })),
+        Isa::decode_at(&mem[..], 42usize)
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Isa::decode_at(&[0xff][..], 0usize)
+    );
+}
+
+#[test]
+fn decoding_past_the_end() {
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_at(&[0xea, 0x4c, 0x12][..], 1usize),
+        "Reading past the end of a slice should be reported as unexpected EOF"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_at(&[0xea][..], 1usize)
+    );
+    let bus = |addr: u8| if addr < 0x80 { Some(0xa9) } else { None };
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_at(&bus, 0x7fu8),
+        "Unmapped addresses should be reported as unexpected EOF"
+    );
+}
+
+#[test]
+fn decoding_unbounded_payloads() {
+    //the payload is longer than the address space, so the bus would be read forever
+    let bus = |addr: u16| match addr {
+        0 => Some(0x03),
+        1 | 2 => Some(0x80),
+        3 => Some(0x08),
+        _ => Some(0xea),
+    };
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_at(&bus, 0u16),
+        "Fetching should stop at the size of the address space"
+    );
+    let reads = std::cell::Cell::new(0usize);
+    let bus = |addr: u8| {
+        reads.set(reads.get() + 1);
+        match addr {
+            0 => Some(0x03),
+            1 => Some(0x82),
+            2 => Some(0x04),
+            _ => Some(0xea),
+        }
+    };
+    assert_eq!(Err(DecodeError::UnexpectedEOF), Isa::decode_at(&bus, 0u8));
+    assert!(reads.get() <= 0x100, "Read {} bytes", reads.get());
+}
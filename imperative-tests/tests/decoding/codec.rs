@@ -0,0 +1,116 @@
+use bytes::BytesMut;
+use futures_util::{SinkExt, StreamExt};
+use imperative_rs::{codec::InstructionCodec, DecodeError, InstructionSet};
+use std::io::ErrorKind;
+use tokio::io::AsyncWriteExt;
+use tokio_util::codec::{Decoder, Encoder, Framed, FramedRead};
+
+#[derive(InstructionSet, Debug, PartialEq, Clone)]
+enum Frame {
+    #[opcode = "0x01"]
+    Ping,
+    #[opcode = "0x02_gr"]
+    Select { g: u8, r: u8 },
+    #[opcode = "0x03_nn"]
+    Write {
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn decoding_frames() {
+    let mut codec = InstructionCodec::<Frame>::new();
+    let mut src = BytesMut::from(&[0x03, 0x02, 0xab][..]);
+    assert_eq!(
+        None,
+        codec.decode(&mut src).unwrap(),
+        "Incomplete frame should wait for more bytes"
+    );
+    assert_eq!(3, src.len(), "Incomplete frame was consumed");
+    src.extend_from_slice(&[0xcd, 0x01]);
+    assert_eq!(
+        Some(Frame::Write {
+            n: 2,
+            data: vec![0xab, 0xcd]
+        }),
+        codec.decode(&mut src).unwrap()
+    );
+    assert_eq!(Some(Frame::Ping), codec.decode(&mut src).unwrap());
+    assert_eq!(None, codec.decode(&mut src).unwrap());
+}
+
+#[test]
+fn unknown_frames_are_errors() {
+    let mut codec = InstructionCodec::<Frame>::new();
+    let err = codec.decode(&mut BytesMut::from(&[0x07][..])).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        Some(&DecodeError::UnknownOpcode),
+        err.get_ref().and_then(|inner| inner.downcast_ref()),
+    );
+}
+
+#[test]
+fn encoding_frames() {
+    let mut codec = InstructionCodec::<Frame>::new();
+    let mut dst = BytesMut::new();
+    codec
+        .encode(Frame::Select { g: 3, r: 7 }, &mut dst)
+        .unwrap();
+    codec.encode(&Frame::Ping, &mut dst).unwrap();
+    assert_eq!(&[0x02, 0x37, 0x01][..], &dst[..]);
+    let err = codec
+        .encode(
+            Frame::Write {
+                n: 2,
+                data: vec![0x00],
+            },
+            &mut dst,
+        )
+        .unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+    assert_eq!(3, dst.len(), "Nothing should be written on error");
+}
+
+#[tokio::test]
+async fn frames_over_duplex_stream() {
+    let (client, server) = tokio::io::duplex(4);
+    let mut client = Framed::new(client, InstructionCodec::<Frame>::new());
+    let mut server = Framed::new(server, InstructionCodec::<Frame>::new());
+    let frames = vec![
+        Frame::Ping,
+        Frame::Write {
+            n: 10,
+            data: (0..10).collect(),
+        },
+        Frame::Select { g: 1, r: 2 },
+    ];
+    let sent = frames.clone();
+    let writer = tokio::spawn(async move {
+        for frame in sent {
+            client.send(frame).await.unwrap();
+        }
+        client
+    });
+    for frame in frames {
+        assert_eq!(frame, server.next().await.unwrap().unwrap());
+    }
+    server.send(Frame::Ping).await.unwrap();
+    let mut client = writer.await.unwrap();
+    assert_eq!(Frame::Ping, client.next().await.unwrap().unwrap());
+}
+
+#[tokio::test]
+async fn truncated_stream_is_an_error() {
+    let (mut client, server) = tokio::io::duplex(16);
+    client.write_all(&[0x01, 0x03, 0x04, 0x00]).await.unwrap();
+    drop(client);
+    let mut server = FramedRead::new(server, InstructionCodec::<Frame>::new());
+    assert_eq!(Frame::Ping, server.next().await.unwrap().unwrap());
+    assert!(
+        server.next().await.unwrap().is_err(),
+        "Stream ending within a frame should be reported"
+    );
+}
@@ -0,0 +1,46 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum I8080 {
+    #[opcode = "0xc3_nnnn"]
+    Jmp {
+        #[endian = "little"]
+        n: u16,
+    },
+    #[opcode = "0x3e_nn"]
+    Mvi {
+        #[endian = "little"]
+        n: u8,
+    },
+    #[opcode = "0xd_nnnnnn_0"]
+    Shifted {
+        #[endian = "little"]
+        n: u32,
+    },
+    #[opcode = "0xe_nnnn"]
+    Big { n: u16 },
+}
+
+#[test]
+fn decoding_little_endian() {
+    assert_eq!(
+        Ok((3, I8080::Jmp { n: 0x1234 })),
+        I8080::decode(&[0xc3, 0x34, 0x12]),
+        "Failed to decode little endian u16"
+    );
+    assert_eq!(
+        Ok((2, I8080::Mvi { n: 0xab })),
+        I8080::decode(&[0x3e, 0xab]),
+        "Failed to decode little endian u8"
+    );
+    assert_eq!(
+        Ok((4, I8080::Shifted { n: 0x123456 })),
+        I8080::decode(&[0xd5, 0x63, 0x41, 0x20]),
+        "Failed to decode little endian variable that isn't aligned to bytes"
+    );
+    assert_eq!(
+        Ok((3, I8080::Big { n: 0x1234 })),
+        I8080::decode(&[0xe1, 0x23, 0x40]),
+        "Failed to decode big endian variable next to little endian ones"
+    );
+}
@@ -0,0 +1,59 @@
+use imperative_rs::{DecodeError, ExtSet, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Ext {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x1x"]
+    #[extension = "M"]
+    Mul { x: u8 },
+    #[opcode = "0x2x"]
+    #[extension = "SCHIP"]
+    Scroll { x: u8 },
+    #[cfg(test)]
+    #[opcode = "0x3x"]
+    #[extension = "SCHIP"]
+    High { x: u8 },
+    #[cfg(not(test))]
+    #[opcode = "0x4x"]
+    Removed { x: u8 },
+}
+
+#[test]
+fn decoding_extensions() {
+    assert_eq!(
+        Ok((1, Ext::Nop)),
+        Ext::decode_for(&[0x00], ExtSet::empty()),
+        "Failed to decode instruction without extension"
+    );
+    assert_eq!(
+        Ok((1, Ext::Mul { x: 0xa })),
+        Ext::decode_for(&[0x1a], Ext::EXT_M),
+        "Failed to decode instruction of enabled extension"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Ext::decode_for(&[0x1a], Ext::EXT_SCHIP),
+        "Decoded instruction of disabled extension"
+    );
+    assert_eq!(
+        Ok((1, Ext::Scroll { x: 0xb })),
+        Ext::decode_for(&[0x2b], Ext::EXT_M | Ext::EXT_SCHIP),
+        "Failed to decode instruction when multiple extensions are enabled"
+    );
+    assert_eq!(
+        Ok((1, Ext::High { x: 0xc })),
+        Ext::decode_for(&[0x3c], ExtSet::all()),
+        "Failed to decode instruction with cfg attribute"
+    );
+    assert_eq!(
+        Ok((1, Ext::Scroll { x: 0xb })),
+        Ext::decode(&[0x2b]),
+        "InstructionSet::decode(..) should decode all extensions"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Ext::decode(&[0x4d]),
+        "Decoded variant that is not compiled"
+    );
+}
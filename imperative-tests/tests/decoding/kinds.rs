@@ -0,0 +1,72 @@
+use imperative_rs::{DecodeError, InstructionSet};
+use std::collections::HashMap;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(kind = "EvmKind")]
+enum Evm {
+    #[opcode = "0x00"]
+    Stop,
+    #[opcode = "0b011nnnnn"]
+    Push {
+        n: u8,
+        #[len = "n + 1"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0x01"]
+    Add,
+    #[opcode = "0x5x"]
+    Jump { x: u8 },
+}
+
+#[test]
+fn classify() {
+    assert_eq!(Ok((1, EvmKind::Stop)), Evm::classify(&[0x00]));
+    assert_eq!(
+        Ok((3, EvmKind::Push)),
+        Evm::classify(&[0x61, 0xab, 0xcd]),
+        "Failed to classify instruction with payload"
+    );
+    assert_eq!(Ok((1, EvmKind::Jump)), Evm::classify(&[0x5a]));
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Evm::classify(&[0xff]),
+        "Unknown opcode should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Evm::classify(&[0x61, 0xab]),
+        "Truncated payload should be reported"
+    );
+}
+
+#[test]
+fn kind() {
+    let mem = [0x00, 0x61, 0xab, 0xcd, 0x01, 0x5a, 0x01, 0x60, 0x12];
+    let mut histogram: HashMap<EvmKind, usize> = HashMap::new();
+    let mut offset = 0;
+    while offset < mem.len() {
+        let (num_bytes, instr) = Evm::decode(&mem[offset..]).unwrap();
+        assert_eq!(
+            Ok((num_bytes, instr.kind())),
+            Evm::classify(&mem[offset..]),
+            "Classified instruction differs from decoded instruction at offset {}",
+            offset
+        );
+        *histogram.entry(instr.kind()).or_default() += 1;
+        offset += num_bytes;
+    }
+    assert_eq!(Some(&2), histogram.get(&EvmKind::Push));
+    assert_eq!(Some(&2), histogram.get(&EvmKind::Add));
+    assert_eq!(Some(&1), histogram.get(&EvmKind::Stop));
+    assert_eq!(Some(&1), histogram.get(&EvmKind::Jump));
+}
+
+#[test]
+fn all_kinds() {
+    assert_eq!(
+        &[EvmKind::Stop, EvmKind::Push, EvmKind::Add, EvmKind::Jump],
+        EvmKind::ALL,
+        "Kinds should be listed in order of declaration"
+    );
+    assert!(EvmKind::Stop < EvmKind::Jump, "Kinds should be ordered");
+}
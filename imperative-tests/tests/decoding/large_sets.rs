@@ -0,0 +1,678 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+//the weightless instructions with variables in the first bits are copied into every arm of
+//the other instructions until they are the only ones left, so their arm is shared
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Shared {
+    #[opcode = "0b0000_xxxx"]
+    #[weight = 1]
+    A { x: u8 },
+    #[opcode = "0b0100_xxxx"]
+    #[weight = 1]
+    B { x: u8 },
+    #[opcode = "0b1000_xxxx"]
+    #[weight = 1]
+    C { x: u8 },
+    #[opcode = "0b1100_xxxx"]
+    #[weight = 1]
+    D { x: u8 },
+    #[opcode = "0bxx10_xxxx"]
+    #[weight = 0]
+    S { x: u8 },
+    #[opcode = "0bxx11_xxxx"]
+    #[weight = 0]
+    T { x: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Large {
+    #[opcode = "0b1_000000000_xxxxxx"]
+    I0 { x: u8 },
+    #[opcode = "0b1_000000001_xxxxxx"]
+    I1 { x: u8 },
+    #[opcode = "0b1_000000010_xxxxxx"]
+    I2 { x: u8 },
+    #[opcode = "0b1_000000011_xxxxxx"]
+    I3 { x: u8 },
+    #[opcode = "0b1_000000100_xxxxxx"]
+    I4 { x: u8 },
+    #[opcode = "0b1_000000101_xxxxxx"]
+    I5 { x: u8 },
+    #[opcode = "0b1_000000110_xxxxxx"]
+    I6 { x: u8 },
+    #[opcode = "0b1_000000111_xxxxxx"]
+    I7 { x: u8 },
+    #[opcode = "0b1_000001000_xxxxxx"]
+    I8 { x: u8 },
+    #[opcode = "0b1_000001001_xxxxxx"]
+    I9 { x: u8 },
+    #[opcode = "0b1_000001010_xxxxxx"]
+    I10 { x: u8 },
+    #[opcode = "0b1_000001011_xxxxxx"]
+    I11 { x: u8 },
+    #[opcode = "0b1_000001100_xxxxxx"]
+    I12 { x: u8 },
+    #[opcode = "0b1_000001101_xxxxxx"]
+    I13 { x: u8 },
+    #[opcode = "0b1_000001110_xxxxxx"]
+    I14 { x: u8 },
+    #[opcode = "0b1_000001111_xxxxxx"]
+    I15 { x: u8 },
+    #[opcode = "0b1_000010000_xxxxxx"]
+    I16 { x: u8 },
+    #[opcode = "0b1_000010001_xxxxxx"]
+    I17 { x: u8 },
+    #[opcode = "0b1_000010010_xxxxxx"]
+    I18 { x: u8 },
+    #[opcode = "0b1_000010011_xxxxxx"]
+    I19 { x: u8 },
+    #[opcode = "0b1_000010100_xxxxxx"]
+    I20 { x: u8 },
+    #[opcode = "0b1_000010101_xxxxxx"]
+    I21 { x: u8 },
+    #[opcode = "0b1_000010110_xxxxxx"]
+    I22 { x: u8 },
+    #[opcode = "0b1_000010111_xxxxxx"]
+    I23 { x: u8 },
+    #[opcode = "0b1_000011000_xxxxxx"]
+    I24 { x: u8 },
+    #[opcode = "0b1_000011001_xxxxxx"]
+    I25 { x: u8 },
+    #[opcode = "0b1_000011010_xxxxxx"]
+    I26 { x: u8 },
+    #[opcode = "0b1_000011011_xxxxxx"]
+    I27 { x: u8 },
+    #[opcode = "0b1_000011100_xxxxxx"]
+    I28 { x: u8 },
+    #[opcode = "0b1_000011101_xxxxxx"]
+    I29 { x: u8 },
+    #[opcode = "0b1_000011110_xxxxxx"]
+    I30 { x: u8 },
+    #[opcode = "0b1_000011111_xxxxxx"]
+    I31 { x: u8 },
+    #[opcode = "0b1_000100000_xxxxxx"]
+    I32 { x: u8 },
+    #[opcode = "0b1_000100001_xxxxxx"]
+    I33 { x: u8 },
+    #[opcode = "0b1_000100010_xxxxxx"]
+    I34 { x: u8 },
+    #[opcode = "0b1_000100011_xxxxxx"]
+    I35 { x: u8 },
+    #[opcode = "0b1_000100100_xxxxxx"]
+    I36 { x: u8 },
+    #[opcode = "0b1_000100101_xxxxxx"]
+    I37 { x: u8 },
+    #[opcode = "0b1_000100110_xxxxxx"]
+    I38 { x: u8 },
+    #[opcode = "0b1_000100111_xxxxxx"]
+    I39 { x: u8 },
+    #[opcode = "0b1_000101000_xxxxxx"]
+    I40 { x: u8 },
+    #[opcode = "0b1_000101001_xxxxxx"]
+    I41 { x: u8 },
+    #[opcode = "0b1_000101010_xxxxxx"]
+    I42 { x: u8 },
+    #[opcode = "0b1_000101011_xxxxxx"]
+    I43 { x: u8 },
+    #[opcode = "0b1_000101100_xxxxxx"]
+    I44 { x: u8 },
+    #[opcode = "0b1_000101101_xxxxxx"]
+    I45 { x: u8 },
+    #[opcode = "0b1_000101110_xxxxxx"]
+    I46 { x: u8 },
+    #[opcode = "0b1_000101111_xxxxxx"]
+    I47 { x: u8 },
+    #[opcode = "0b1_000110000_xxxxxx"]
+    I48 { x: u8 },
+    #[opcode = "0b1_000110001_xxxxxx"]
+    I49 { x: u8 },
+    #[opcode = "0b1_000110010_xxxxxx"]
+    I50 { x: u8 },
+    #[opcode = "0b1_000110011_xxxxxx"]
+    I51 { x: u8 },
+    #[opcode = "0b1_000110100_xxxxxx"]
+    I52 { x: u8 },
+    #[opcode = "0b1_000110101_xxxxxx"]
+    I53 { x: u8 },
+    #[opcode = "0b1_000110110_xxxxxx"]
+    I54 { x: u8 },
+    #[opcode = "0b1_000110111_xxxxxx"]
+    I55 { x: u8 },
+    #[opcode = "0b1_000111000_xxxxxx"]
+    I56 { x: u8 },
+    #[opcode = "0b1_000111001_xxxxxx"]
+    I57 { x: u8 },
+    #[opcode = "0b1_000111010_xxxxxx"]
+    I58 { x: u8 },
+    #[opcode = "0b1_000111011_xxxxxx"]
+    I59 { x: u8 },
+    #[opcode = "0b1_000111100_xxxxxx"]
+    I60 { x: u8 },
+    #[opcode = "0b1_000111101_xxxxxx"]
+    I61 { x: u8 },
+    #[opcode = "0b1_000111110_xxxxxx"]
+    I62 { x: u8 },
+    #[opcode = "0b1_000111111_xxxxxx"]
+    I63 { x: u8 },
+    #[opcode = "0b1_001000000_xxxxxx"]
+    I64 { x: u8 },
+    #[opcode = "0b1_001000001_xxxxxx"]
+    I65 { x: u8 },
+    #[opcode = "0b1_001000010_xxxxxx"]
+    I66 { x: u8 },
+    #[opcode = "0b1_001000011_xxxxxx"]
+    I67 { x: u8 },
+    #[opcode = "0b1_001000100_xxxxxx"]
+    I68 { x: u8 },
+    #[opcode = "0b1_001000101_xxxxxx"]
+    I69 { x: u8 },
+    #[opcode = "0b1_001000110_xxxxxx"]
+    I70 { x: u8 },
+    #[opcode = "0b1_001000111_xxxxxx"]
+    I71 { x: u8 },
+    #[opcode = "0b1_001001000_xxxxxx"]
+    I72 { x: u8 },
+    #[opcode = "0b1_001001001_xxxxxx"]
+    I73 { x: u8 },
+    #[opcode = "0b1_001001010_xxxxxx"]
+    I74 { x: u8 },
+    #[opcode = "0b1_001001011_xxxxxx"]
+    I75 { x: u8 },
+    #[opcode = "0b1_001001100_xxxxxx"]
+    I76 { x: u8 },
+    #[opcode = "0b1_001001101_xxxxxx"]
+    I77 { x: u8 },
+    #[opcode = "0b1_001001110_xxxxxx"]
+    I78 { x: u8 },
+    #[opcode = "0b1_001001111_xxxxxx"]
+    I79 { x: u8 },
+    #[opcode = "0b1_001010000_xxxxxx"]
+    I80 { x: u8 },
+    #[opcode = "0b1_001010001_xxxxxx"]
+    I81 { x: u8 },
+    #[opcode = "0b1_001010010_xxxxxx"]
+    I82 { x: u8 },
+    #[opcode = "0b1_001010011_xxxxxx"]
+    I83 { x: u8 },
+    #[opcode = "0b1_001010100_xxxxxx"]
+    I84 { x: u8 },
+    #[opcode = "0b1_001010101_xxxxxx"]
+    I85 { x: u8 },
+    #[opcode = "0b1_001010110_xxxxxx"]
+    I86 { x: u8 },
+    #[opcode = "0b1_001010111_xxxxxx"]
+    I87 { x: u8 },
+    #[opcode = "0b1_001011000_xxxxxx"]
+    I88 { x: u8 },
+    #[opcode = "0b1_001011001_xxxxxx"]
+    I89 { x: u8 },
+    #[opcode = "0b1_001011010_xxxxxx"]
+    I90 { x: u8 },
+    #[opcode = "0b1_001011011_xxxxxx"]
+    I91 { x: u8 },
+    #[opcode = "0b1_001011100_xxxxxx"]
+    I92 { x: u8 },
+    #[opcode = "0b1_001011101_xxxxxx"]
+    I93 { x: u8 },
+    #[opcode = "0b1_001011110_xxxxxx"]
+    I94 { x: u8 },
+    #[opcode = "0b1_001011111_xxxxxx"]
+    I95 { x: u8 },
+    #[opcode = "0b1_001100000_xxxxxx"]
+    I96 { x: u8 },
+    #[opcode = "0b1_001100001_xxxxxx"]
+    I97 { x: u8 },
+    #[opcode = "0b1_001100010_xxxxxx"]
+    I98 { x: u8 },
+    #[opcode = "0b1_001100011_xxxxxx"]
+    I99 { x: u8 },
+    #[opcode = "0b1_001100100_xxxxxx"]
+    I100 { x: u8 },
+    #[opcode = "0b1_001100101_xxxxxx"]
+    I101 { x: u8 },
+    #[opcode = "0b1_001100110_xxxxxx"]
+    I102 { x: u8 },
+    #[opcode = "0b1_001100111_xxxxxx"]
+    I103 { x: u8 },
+    #[opcode = "0b1_001101000_xxxxxx"]
+    I104 { x: u8 },
+    #[opcode = "0b1_001101001_xxxxxx"]
+    I105 { x: u8 },
+    #[opcode = "0b1_001101010_xxxxxx"]
+    I106 { x: u8 },
+    #[opcode = "0b1_001101011_xxxxxx"]
+    I107 { x: u8 },
+    #[opcode = "0b1_001101100_xxxxxx"]
+    I108 { x: u8 },
+    #[opcode = "0b1_001101101_xxxxxx"]
+    I109 { x: u8 },
+    #[opcode = "0b1_001101110_xxxxxx"]
+    I110 { x: u8 },
+    #[opcode = "0b1_001101111_xxxxxx"]
+    I111 { x: u8 },
+    #[opcode = "0b1_001110000_xxxxxx"]
+    I112 { x: u8 },
+    #[opcode = "0b1_001110001_xxxxxx"]
+    I113 { x: u8 },
+    #[opcode = "0b1_001110010_xxxxxx"]
+    I114 { x: u8 },
+    #[opcode = "0b1_001110011_xxxxxx"]
+    I115 { x: u8 },
+    #[opcode = "0b1_001110100_xxxxxx"]
+    I116 { x: u8 },
+    #[opcode = "0b1_001110101_xxxxxx"]
+    I117 { x: u8 },
+    #[opcode = "0b1_001110110_xxxxxx"]
+    I118 { x: u8 },
+    #[opcode = "0b1_001110111_xxxxxx"]
+    I119 { x: u8 },
+    #[opcode = "0b1_001111000_xxxxxx"]
+    I120 { x: u8 },
+    #[opcode = "0b1_001111001_xxxxxx"]
+    I121 { x: u8 },
+    #[opcode = "0b1_001111010_xxxxxx"]
+    I122 { x: u8 },
+    #[opcode = "0b1_001111011_xxxxxx"]
+    I123 { x: u8 },
+    #[opcode = "0b1_001111100_xxxxxx"]
+    I124 { x: u8 },
+    #[opcode = "0b1_001111101_xxxxxx"]
+    I125 { x: u8 },
+    #[opcode = "0b1_001111110_xxxxxx"]
+    I126 { x: u8 },
+    #[opcode = "0b1_001111111_xxxxxx"]
+    I127 { x: u8 },
+    #[opcode = "0b1_010000000_xxxxxx"]
+    I128 { x: u8 },
+    #[opcode = "0b1_010000001_xxxxxx"]
+    I129 { x: u8 },
+    #[opcode = "0b1_010000010_xxxxxx"]
+    I130 { x: u8 },
+    #[opcode = "0b1_010000011_xxxxxx"]
+    I131 { x: u8 },
+    #[opcode = "0b1_010000100_xxxxxx"]
+    I132 { x: u8 },
+    #[opcode = "0b1_010000101_xxxxxx"]
+    I133 { x: u8 },
+    #[opcode = "0b1_010000110_xxxxxx"]
+    I134 { x: u8 },
+    #[opcode = "0b1_010000111_xxxxxx"]
+    I135 { x: u8 },
+    #[opcode = "0b1_010001000_xxxxxx"]
+    I136 { x: u8 },
+    #[opcode = "0b1_010001001_xxxxxx"]
+    I137 { x: u8 },
+    #[opcode = "0b1_010001010_xxxxxx"]
+    I138 { x: u8 },
+    #[opcode = "0b1_010001011_xxxxxx"]
+    I139 { x: u8 },
+    #[opcode = "0b1_010001100_xxxxxx"]
+    I140 { x: u8 },
+    #[opcode = "0b1_010001101_xxxxxx"]
+    I141 { x: u8 },
+    #[opcode = "0b1_010001110_xxxxxx"]
+    I142 { x: u8 },
+    #[opcode = "0b1_010001111_xxxxxx"]
+    I143 { x: u8 },
+    #[opcode = "0b1_010010000_xxxxxx"]
+    I144 { x: u8 },
+    #[opcode = "0b1_010010001_xxxxxx"]
+    I145 { x: u8 },
+    #[opcode = "0b1_010010010_xxxxxx"]
+    I146 { x: u8 },
+    #[opcode = "0b1_010010011_xxxxxx"]
+    I147 { x: u8 },
+    #[opcode = "0b1_010010100_xxxxxx"]
+    I148 { x: u8 },
+    #[opcode = "0b1_010010101_xxxxxx"]
+    I149 { x: u8 },
+    #[opcode = "0b1_010010110_xxxxxx"]
+    I150 { x: u8 },
+    #[opcode = "0b1_010010111_xxxxxx"]
+    I151 { x: u8 },
+    #[opcode = "0b1_010011000_xxxxxx"]
+    I152 { x: u8 },
+    #[opcode = "0b1_010011001_xxxxxx"]
+    I153 { x: u8 },
+    #[opcode = "0b1_010011010_xxxxxx"]
+    I154 { x: u8 },
+    #[opcode = "0b1_010011011_xxxxxx"]
+    I155 { x: u8 },
+    #[opcode = "0b1_010011100_xxxxxx"]
+    I156 { x: u8 },
+    #[opcode = "0b1_010011101_xxxxxx"]
+    I157 { x: u8 },
+    #[opcode = "0b1_010011110_xxxxxx"]
+    I158 { x: u8 },
+    #[opcode = "0b1_010011111_xxxxxx"]
+    I159 { x: u8 },
+    #[opcode = "0b1_010100000_xxxxxx"]
+    I160 { x: u8 },
+    #[opcode = "0b1_010100001_xxxxxx"]
+    I161 { x: u8 },
+    #[opcode = "0b1_010100010_xxxxxx"]
+    I162 { x: u8 },
+    #[opcode = "0b1_010100011_xxxxxx"]
+    I163 { x: u8 },
+    #[opcode = "0b1_010100100_xxxxxx"]
+    I164 { x: u8 },
+    #[opcode = "0b1_010100101_xxxxxx"]
+    I165 { x: u8 },
+    #[opcode = "0b1_010100110_xxxxxx"]
+    I166 { x: u8 },
+    #[opcode = "0b1_010100111_xxxxxx"]
+    I167 { x: u8 },
+    #[opcode = "0b1_010101000_xxxxxx"]
+    I168 { x: u8 },
+    #[opcode = "0b1_010101001_xxxxxx"]
+    I169 { x: u8 },
+    #[opcode = "0b1_010101010_xxxxxx"]
+    I170 { x: u8 },
+    #[opcode = "0b1_010101011_xxxxxx"]
+    I171 { x: u8 },
+    #[opcode = "0b1_010101100_xxxxxx"]
+    I172 { x: u8 },
+    #[opcode = "0b1_010101101_xxxxxx"]
+    I173 { x: u8 },
+    #[opcode = "0b1_010101110_xxxxxx"]
+    I174 { x: u8 },
+    #[opcode = "0b1_010101111_xxxxxx"]
+    I175 { x: u8 },
+    #[opcode = "0b1_010110000_xxxxxx"]
+    I176 { x: u8 },
+    #[opcode = "0b1_010110001_xxxxxx"]
+    I177 { x: u8 },
+    #[opcode = "0b1_010110010_xxxxxx"]
+    I178 { x: u8 },
+    #[opcode = "0b1_010110011_xxxxxx"]
+    I179 { x: u8 },
+    #[opcode = "0b1_010110100_xxxxxx"]
+    I180 { x: u8 },
+    #[opcode = "0b1_010110101_xxxxxx"]
+    I181 { x: u8 },
+    #[opcode = "0b1_010110110_xxxxxx"]
+    I182 { x: u8 },
+    #[opcode = "0b1_010110111_xxxxxx"]
+    I183 { x: u8 },
+    #[opcode = "0b1_010111000_xxxxxx"]
+    I184 { x: u8 },
+    #[opcode = "0b1_010111001_xxxxxx"]
+    I185 { x: u8 },
+    #[opcode = "0b1_010111010_xxxxxx"]
+    I186 { x: u8 },
+    #[opcode = "0b1_010111011_xxxxxx"]
+    I187 { x: u8 },
+    #[opcode = "0b1_010111100_xxxxxx"]
+    I188 { x: u8 },
+    #[opcode = "0b1_010111101_xxxxxx"]
+    I189 { x: u8 },
+    #[opcode = "0b1_010111110_xxxxxx"]
+    I190 { x: u8 },
+    #[opcode = "0b1_010111111_xxxxxx"]
+    I191 { x: u8 },
+    #[opcode = "0b1_011000000_xxxxxx"]
+    I192 { x: u8 },
+    #[opcode = "0b1_011000001_xxxxxx"]
+    I193 { x: u8 },
+    #[opcode = "0b1_011000010_xxxxxx"]
+    I194 { x: u8 },
+    #[opcode = "0b1_011000011_xxxxxx"]
+    I195 { x: u8 },
+    #[opcode = "0b1_011000100_xxxxxx"]
+    I196 { x: u8 },
+    #[opcode = "0b1_011000101_xxxxxx"]
+    I197 { x: u8 },
+    #[opcode = "0b1_011000110_xxxxxx"]
+    I198 { x: u8 },
+    #[opcode = "0b1_011000111_xxxxxx"]
+    I199 { x: u8 },
+    #[opcode = "0b1_011001000_xxxxxx"]
+    I200 { x: u8 },
+    #[opcode = "0b1_011001001_xxxxxx"]
+    I201 { x: u8 },
+    #[opcode = "0b1_011001010_xxxxxx"]
+    I202 { x: u8 },
+    #[opcode = "0b1_011001011_xxxxxx"]
+    I203 { x: u8 },
+    #[opcode = "0b1_011001100_xxxxxx"]
+    I204 { x: u8 },
+    #[opcode = "0b1_011001101_xxxxxx"]
+    I205 { x: u8 },
+    #[opcode = "0b1_011001110_xxxxxx"]
+    I206 { x: u8 },
+    #[opcode = "0b1_011001111_xxxxxx"]
+    I207 { x: u8 },
+    #[opcode = "0b1_011010000_xxxxxx"]
+    I208 { x: u8 },
+    #[opcode = "0b1_011010001_xxxxxx"]
+    I209 { x: u8 },
+    #[opcode = "0b1_011010010_xxxxxx"]
+    I210 { x: u8 },
+    #[opcode = "0b1_011010011_xxxxxx"]
+    I211 { x: u8 },
+    #[opcode = "0b1_011010100_xxxxxx"]
+    I212 { x: u8 },
+    #[opcode = "0b1_011010101_xxxxxx"]
+    I213 { x: u8 },
+    #[opcode = "0b1_011010110_xxxxxx"]
+    I214 { x: u8 },
+    #[opcode = "0b1_011010111_xxxxxx"]
+    I215 { x: u8 },
+    #[opcode = "0b1_011011000_xxxxxx"]
+    I216 { x: u8 },
+    #[opcode = "0b1_011011001_xxxxxx"]
+    I217 { x: u8 },
+    #[opcode = "0b1_011011010_xxxxxx"]
+    I218 { x: u8 },
+    #[opcode = "0b1_011011011_xxxxxx"]
+    I219 { x: u8 },
+    #[opcode = "0b1_011011100_xxxxxx"]
+    I220 { x: u8 },
+    #[opcode = "0b1_011011101_xxxxxx"]
+    I221 { x: u8 },
+    #[opcode = "0b1_011011110_xxxxxx"]
+    I222 { x: u8 },
+    #[opcode = "0b1_011011111_xxxxxx"]
+    I223 { x: u8 },
+    #[opcode = "0b1_011100000_xxxxxx"]
+    I224 { x: u8 },
+    #[opcode = "0b1_011100001_xxxxxx"]
+    I225 { x: u8 },
+    #[opcode = "0b1_011100010_xxxxxx"]
+    I226 { x: u8 },
+    #[opcode = "0b1_011100011_xxxxxx"]
+    I227 { x: u8 },
+    #[opcode = "0b1_011100100_xxxxxx"]
+    I228 { x: u8 },
+    #[opcode = "0b1_011100101_xxxxxx"]
+    I229 { x: u8 },
+    #[opcode = "0b1_011100110_xxxxxx"]
+    I230 { x: u8 },
+    #[opcode = "0b1_011100111_xxxxxx"]
+    I231 { x: u8 },
+    #[opcode = "0b1_011101000_xxxxxx"]
+    I232 { x: u8 },
+    #[opcode = "0b1_011101001_xxxxxx"]
+    I233 { x: u8 },
+    #[opcode = "0b1_011101010_xxxxxx"]
+    I234 { x: u8 },
+    #[opcode = "0b1_011101011_xxxxxx"]
+    I235 { x: u8 },
+    #[opcode = "0b1_011101100_xxxxxx"]
+    I236 { x: u8 },
+    #[opcode = "0b1_011101101_xxxxxx"]
+    I237 { x: u8 },
+    #[opcode = "0b1_011101110_xxxxxx"]
+    I238 { x: u8 },
+    #[opcode = "0b1_011101111_xxxxxx"]
+    I239 { x: u8 },
+    #[opcode = "0b1_011110000_xxxxxx"]
+    I240 { x: u8 },
+    #[opcode = "0b1_011110001_xxxxxx"]
+    I241 { x: u8 },
+    #[opcode = "0b1_011110010_xxxxxx"]
+    I242 { x: u8 },
+    #[opcode = "0b1_011110011_xxxxxx"]
+    I243 { x: u8 },
+    #[opcode = "0b1_011110100_xxxxxx"]
+    I244 { x: u8 },
+    #[opcode = "0b1_011110101_xxxxxx"]
+    I245 { x: u8 },
+    #[opcode = "0b1_011110110_xxxxxx"]
+    I246 { x: u8 },
+    #[opcode = "0b1_011110111_xxxxxx"]
+    I247 { x: u8 },
+    #[opcode = "0b1_011111000_xxxxxx"]
+    I248 { x: u8 },
+    #[opcode = "0b1_011111001_xxxxxx"]
+    I249 { x: u8 },
+    #[opcode = "0b1_011111010_xxxxxx"]
+    I250 { x: u8 },
+    #[opcode = "0b1_011111011_xxxxxx"]
+    I251 { x: u8 },
+    #[opcode = "0b1_011111100_xxxxxx"]
+    I252 { x: u8 },
+    #[opcode = "0b1_011111101_xxxxxx"]
+    I253 { x: u8 },
+    #[opcode = "0b1_011111110_xxxxxx"]
+    I254 { x: u8 },
+    #[opcode = "0b1_011111111_xxxxxx"]
+    I255 { x: u8 },
+    #[opcode = "0b1_100000000_xxxxxx"]
+    I256 { x: u8 },
+    #[opcode = "0b1_100000001_xxxxxx"]
+    I257 { x: u8 },
+    #[opcode = "0b1_100000010_xxxxxx"]
+    I258 { x: u8 },
+    #[opcode = "0b1_100000011_xxxxxx"]
+    I259 { x: u8 },
+    #[opcode = "0b1_100000100_xxxxxx"]
+    I260 { x: u8 },
+    #[opcode = "0b1_100000101_xxxxxx"]
+    I261 { x: u8 },
+    #[opcode = "0b1_100000110_xxxxxx"]
+    I262 { x: u8 },
+    #[opcode = "0b1_100000111_xxxxxx"]
+    I263 { x: u8 },
+    #[opcode = "0b1_100001000_xxxxxx"]
+    I264 { x: u8 },
+    #[opcode = "0b1_100001001_xxxxxx"]
+    I265 { x: u8 },
+    #[opcode = "0b1_100001010_xxxxxx"]
+    I266 { x: u8 },
+    #[opcode = "0b1_100001011_xxxxxx"]
+    I267 { x: u8 },
+    #[opcode = "0b1_100001100_xxxxxx"]
+    I268 { x: u8 },
+    #[opcode = "0b1_100001101_xxxxxx"]
+    I269 { x: u8 },
+    #[opcode = "0b1_100001110_xxxxxx"]
+    I270 { x: u8 },
+    #[opcode = "0b1_100001111_xxxxxx"]
+    I271 { x: u8 },
+    #[opcode = "0b1_100010000_xxxxxx"]
+    I272 { x: u8 },
+    #[opcode = "0b1_100010001_xxxxxx"]
+    I273 { x: u8 },
+    #[opcode = "0b1_100010010_xxxxxx"]
+    I274 { x: u8 },
+    #[opcode = "0b1_100010011_xxxxxx"]
+    I275 { x: u8 },
+    #[opcode = "0b1_100010100_xxxxxx"]
+    I276 { x: u8 },
+    #[opcode = "0b1_100010101_xxxxxx"]
+    I277 { x: u8 },
+    #[opcode = "0b1_100010110_xxxxxx"]
+    I278 { x: u8 },
+    #[opcode = "0b1_100010111_xxxxxx"]
+    I279 { x: u8 },
+    #[opcode = "0b1_100011000_xxxxxx"]
+    I280 { x: u8 },
+    #[opcode = "0b1_100011001_xxxxxx"]
+    I281 { x: u8 },
+    #[opcode = "0b1_100011010_xxxxxx"]
+    I282 { x: u8 },
+    #[opcode = "0b1_100011011_xxxxxx"]
+    I283 { x: u8 },
+    #[opcode = "0b1_100011100_xxxxxx"]
+    I284 { x: u8 },
+    #[opcode = "0b1_100011101_xxxxxx"]
+    I285 { x: u8 },
+    #[opcode = "0b1_100011110_xxxxxx"]
+    I286 { x: u8 },
+    #[opcode = "0b1_100011111_xxxxxx"]
+    I287 { x: u8 },
+    #[opcode = "0b1_100100000_xxxxxx"]
+    I288 { x: u8 },
+    #[opcode = "0b1_100100001_xxxxxx"]
+    I289 { x: u8 },
+    #[opcode = "0b1_100100010_xxxxxx"]
+    I290 { x: u8 },
+    #[opcode = "0b1_100100011_xxxxxx"]
+    I291 { x: u8 },
+    #[opcode = "0b1_100100100_xxxxxx"]
+    I292 { x: u8 },
+    #[opcode = "0b1_100100101_xxxxxx"]
+    I293 { x: u8 },
+    #[opcode = "0b1_100100110_xxxxxx"]
+    I294 { x: u8 },
+    #[opcode = "0b1_100100111_xxxxxx"]
+    I295 { x: u8 },
+    #[opcode = "0b1_100101000_xxxxxx"]
+    I296 { x: u8 },
+    #[opcode = "0b1_100101001_xxxxxx"]
+    I297 { x: u8 },
+    #[opcode = "0b1_100101010_xxxxxx"]
+    I298 { x: u8 },
+    #[opcode = "0b1_100101011_xxxxxx"]
+    I299 { x: u8 },
+    #[opcode = "0b0xxx_xxxx_xxxx_xxxx"]
+    Imm { x: u16 },
+}
+
+#[test]
+fn decoding_shared_arms() {
+    for first in 0..=255u8 {
+        let x = first & 0x0f;
+        let expected = match first >> 4 {
+            0b0000 => Shared::A { x },
+            0b0100 => Shared::B { x },
+            0b1000 => Shared::C { x },
+            0b1100 => Shared::D { x },
+            code if code & 0b11 == 0b10 => Shared::S {
+                x: (first >> 6) << 4 | x,
+            },
+            code if code & 0b11 == 0b11 => Shared::T {
+                x: (first >> 6) << 4 | x,
+            },
+            _ => {
+                assert_eq!(Err(DecodeError::UnknownOpcode), Shared::decode(&[first]));
+                continue;
+            }
+        };
+        assert_eq!(
+            Ok((1, expected)),
+            Shared::decode(&[first]),
+            "Failed to decode {:x}",
+            first
+        );
+    }
+}
+
+#[test]
+fn decoding_large_sets() {
+    for idx in 0..300u16 {
+        let code = 0x8000 | idx << 6 | 0x15;
+        let (num_bytes, instr) = Large::decode(&code.to_be_bytes()).unwrap();
+        assert_eq!(2, num_bytes);
+        assert_eq!(format!("I{} {{ x: 21 }}", idx), format!("{:?}", instr));
+    }
+    assert_eq!(
+        Ok((2, Large::Imm { x: 0x1234 })),
+        Large::decode(&[0x12, 0x34])
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Large::decode(&(0x8000u16 | 300 << 6).to_be_bytes())
+    );
+}
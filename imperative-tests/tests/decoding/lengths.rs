@@ -0,0 +1,129 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum ModRm {
+    #[opcode = "0b11rrrmmm"]
+    Reg { r: u8, m: u8 },
+    #[opcode = "0b01rrrmmm_iiiiiiii"]
+    Disp { r: u8, m: u8, i: i8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Fixed {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x1x_xx"]
+    Load { x: u16 },
+    #[opcode = "0x2f"]
+    Mov {
+        #[operand]
+        modrm: ModRm,
+    },
+    #[opcode = "0x3f"]
+    Br {
+        #[varint = "uleb128"]
+        label: u32,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Evm {
+    #[opcode = "0x00"]
+    Stop,
+    #[opcode = "0b011nnnnn"]
+    Push {
+        n: u8,
+        #[len = "n + 1"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0xf1"]
+    Data {
+        #[varint = "uleb128"]
+        l: u16,
+        #[len = "l"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn instruction_len() {
+    assert_eq!(Ok(1), Fixed::instruction_len(&[0x00]));
+    assert_eq!(Ok(2), Fixed::instruction_len(&[0x1a, 0xbc]));
+    assert_eq!(
+        Ok(2),
+        Fixed::instruction_len(&[0x2f, 0b11_000_001]),
+        "Failed to find length of operand"
+    );
+    assert_eq!(
+        Ok(3),
+        Fixed::instruction_len(&[0x2f, 0b01_000_001, 0x12]),
+        "Failed to find length of operand"
+    );
+    assert_eq!(
+        Ok(4),
+        Fixed::instruction_len(&[0x3f, 0xe5, 0x8e, 0x26]),
+        "Failed to find length of varint"
+    );
+    assert_eq!(
+        Ok(5),
+        Evm::instruction_len(&[0x63, 0x01, 0x02, 0x03, 0x04, 0x00]),
+        "Failed to find length of payload depending on a variable"
+    );
+    assert_eq!(
+        Ok(4),
+        Evm::instruction_len(&[0xf1, 0x02, 0xab, 0xcd]),
+        "Failed to find length of payload depending on a varint"
+    );
+}
+
+#[test]
+fn instruction_len_errors() {
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Fixed::instruction_len(&[0x40]),
+        "Unknown opcode should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Fixed::instruction_len(&[0x2f, 0x00]),
+        "Unknown opcode of operand should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Evm::instruction_len(&[0x63, 0x01, 0x02]),
+        "Truncated payload should be reported"
+    );
+}
+
+#[test]
+fn instruction_len_matches_decode() {
+    let mem = [
+        0x00, 0x61, 0xab, 0xcd, 0xf1, 0x01, 0xff, 0x60, 0x12, 0xf1, 0x00, 0x00,
+    ];
+    let mut offset = 0;
+    while offset < mem.len() {
+        let (num_bytes, _) = Evm::decode(&mem[offset..]).unwrap();
+        assert_eq!(
+            Ok(num_bytes),
+            Evm::instruction_len(&mem[offset..]),
+            "Length differs from decoded length at offset {}",
+            offset
+        );
+        offset += num_bytes;
+    }
+    assert_eq!(mem.len(), offset);
+}
+
+#[test]
+fn min_and_max_len() {
+    assert_eq!(1, ModRm::MIN_LEN);
+    assert_eq!(Some(2), ModRm::MAX_LEN);
+    assert_eq!(1, Fixed::MIN_LEN);
+    assert_eq!(
+        Some(6),
+        Fixed::MAX_LEN,
+        "A uleb128 encoded u32 has at most 5 bytes"
+    );
+    assert_eq!(1, Evm::MIN_LEN);
+    assert_eq!(None, Evm::MAX_LEN, "Payloads have no upper bound");
+}
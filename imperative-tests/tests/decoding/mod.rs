@@ -1,4 +1,26 @@
 mod bin_opcodes;
+mod bitstream;
+mod bundles;
+mod bus;
+mod codec;
+mod endianness;
+mod extensions;
 mod hex_opcodes;
+mod kinds;
+mod large_sets;
+mod lengths;
+mod modes;
+mod operands;
+mod payloads;
+mod prefixes;
+mod split;
 mod star_opcodes;
+mod streams;
+mod tables;
+mod unchecked;
 mod variable_length;
+mod varints;
+mod weights;
+mod wide_opcodes;
+mod word_bits;
+mod words;
@@ -0,0 +1,139 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(PartialEq)]
+enum Mode {
+    Arm,
+    Thumb,
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(context = "Mode")]
+enum Modal {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x1x"]
+    #[when = "*ctx == Mode::Arm"]
+    ArmAdd { x: u8 },
+    #[opcode = "0x1x"]
+    #[when = "*ctx == Mode::Thumb"]
+    ThumbSub { x: u8 },
+    #[opcode = "0x2x"]
+    #[when = "*ctx == Mode::Thumb"]
+    ThumbMov { x: u8 },
+}
+
+#[test]
+fn decoding_with_context() {
+    let (num_bytes, instr) = Modal::decode_with(&[0x00], &Mode::Arm).unwrap();
+    assert_eq!(1, num_bytes, "Reported wrong number of bytes in arm mode");
+    assert_eq!(
+        Modal::Nop,
+        instr,
+        "Failed to decode unconditional instruction in arm mode"
+    );
+    let (_, instr) = Modal::decode_with(&[0x00], &Mode::Thumb).unwrap();
+    assert_eq!(
+        Modal::Nop,
+        instr,
+        "Failed to decode unconditional instruction in thumb mode"
+    );
+
+    let (_, instr) = Modal::decode_with(&[0x1a], &Mode::Arm).unwrap();
+    assert_eq!(
+        Modal::ArmAdd { x: 0xa },
+        instr,
+        "Decoded shared opcode incorrectly in arm mode"
+    );
+    let (_, instr) = Modal::decode_with(&[0x1a], &Mode::Thumb).unwrap();
+    assert_eq!(
+        Modal::ThumbSub { x: 0xa },
+        instr,
+        "Decoded shared opcode incorrectly in thumb mode"
+    );
+
+    let (_, instr) = Modal::decode_with(&[0x2b], &Mode::Thumb).unwrap();
+    assert_eq!(
+        Modal::ThumbMov { x: 0xb },
+        instr,
+        "Failed to decode thumb only instruction in thumb mode"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Modal::decode_with(&[0x2b], &Mode::Arm),
+        "Decoded thumb only instruction in arm mode"
+    );
+}
+
+#[test]
+fn decoding_without_context() {
+    let (_, instr) = Modal::decode(&[0x00]).unwrap();
+    assert_eq!(
+        Modal::Nop,
+        instr,
+        "Failed to decode unconditional instruction without context"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Modal::decode(&[0x1a]),
+        "Decoded conditional instruction without context"
+    );
+}
+
+struct Flags {
+    long: bool,
+    wide: bool,
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(context = "Flags")]
+enum Overlapping {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x1x"]
+    #[when = "ctx.long"]
+    Long { x: u8 },
+    #[opcode = "0x2x"]
+    #[when = "ctx.wide"]
+    Wide { x: u8 },
+    #[opcode = "0x1x"]
+    #[when = "ctx.wide"]
+    WideShared { x: u8 },
+}
+
+#[test]
+fn decoding_with_overlapping_conditions() {
+    let both = Flags {
+        long: true,
+        wide: true,
+    };
+    assert_eq!(
+        Ok((1, Overlapping::Long { x: 3 })),
+        Overlapping::decode_with(&[0x13], &both),
+        "First condition should take precedence for shared opcodes"
+    );
+    assert_eq!(
+        Ok((1, Overlapping::Wide { x: 3 })),
+        Overlapping::decode_with(&[0x23], &both),
+        "Failed to decode instruction of second condition while both hold"
+    );
+    assert_eq!(
+        Ok((1, Overlapping::Nop)),
+        Overlapping::decode_with(&[0x00], &both),
+    );
+    let wide = Flags {
+        long: false,
+        wide: true,
+    };
+    assert_eq!(
+        Ok((1, Overlapping::WideShared { x: 3 })),
+        Overlapping::decode_with(&[0x13], &wide),
+    );
+    let neither = Flags {
+        long: false,
+        wide: false,
+    };
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Overlapping::decode_with(&[0x23], &neither),
+    );
+}
@@ -0,0 +1,88 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum ModRm {
+    #[opcode = "0b11rrrmmm"]
+    Reg { r: u8, m: u8 },
+    #[opcode = "0b00rrrmmm"]
+    Ind { r: u8, m: u8 },
+    #[opcode = "0b01rrrmmm_iiiiiiii"]
+    Disp { r: u8, m: u8, i: i8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Is {
+    #[opcode = "0x01"]
+    Add {
+        #[operand]
+        modrm: ModRm,
+    },
+    #[opcode = "0x02"]
+    Mov {
+        #[operand]
+        dst: ModRm,
+        #[operand]
+        src: ModRm,
+    },
+    #[opcode = "0x1x"]
+    Inc {
+        x: u8,
+        #[operand]
+        modrm: ModRm,
+    },
+}
+
+#[test]
+fn decoding_operands() {
+    let (num_bytes, instr) = Is::decode(&[0x01, 0b11_010_001]).unwrap();
+    assert_eq!(
+        2, num_bytes,
+        "Reported wrong number of bytes for instruction with one byte operand"
+    );
+    assert_eq!(
+        Is::Add {
+            modrm: ModRm::Reg { r: 2, m: 1 }
+        },
+        instr,
+        "Decoded instruction with operand incorrectly"
+    );
+
+    let (num_bytes, instr) = Is::decode(&[0x02, 0b01_011_100, 0xff, 0b00_001_010]).unwrap();
+    assert_eq!(
+        4, num_bytes,
+        "Reported wrong number of bytes for instruction with two operands"
+    );
+    assert_eq!(
+        Is::Mov {
+            dst: ModRm::Disp { r: 3, m: 4, i: -1 },
+            src: ModRm::Ind { r: 1, m: 2 }
+        },
+        instr,
+        "Decoded instruction with two operands incorrectly"
+    );
+
+    let (num_bytes, instr) = Is::decode(&[0x1a, 0b01_000_000, 0x05]).unwrap();
+    assert_eq!(
+        3, num_bytes,
+        "Reported wrong number of bytes for instruction with variable and operand"
+    );
+    assert_eq!(
+        Is::Inc {
+            x: 0xa,
+            modrm: ModRm::Disp { r: 0, m: 0, i: 5 }
+        },
+        instr,
+        "Decoded instruction with variable and operand incorrectly"
+    );
+
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Is::decode(&[0x01]),
+        "Missing operand should be reported as unexpected EOF"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Is::decode(&[0x01, 0b10_000_000]),
+        "Unknown operand should be reported as unknown opcode"
+    );
+}
@@ -0,0 +1,84 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Evm {
+    #[opcode = "0x00"]
+    Stop,
+    #[opcode = "0b011nnnnn"]
+    Push {
+        n: u8,
+        #[len = "n + 1"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0xf0_ll"]
+    Log {
+        l: u8,
+        #[len = "l"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn decoding_payloads() {
+    assert_eq!(
+        Ok((
+            2,
+            Evm::Push {
+                n: 0,
+                data: vec![0xab]
+            }
+        )),
+        Evm::decode(&[0x60, 0xab, 0x00]),
+        "Failed to decode instruction with one byte payload"
+    );
+    assert_eq!(
+        Ok((
+            4,
+            Evm::Push {
+                n: 2,
+                data: vec![0x01, 0x02, 0x03]
+            }
+        )),
+        Evm::decode(&[0x62, 0x01, 0x02, 0x03]),
+        "Failed to decode instruction with three byte payload"
+    );
+    assert_eq!(
+        Ok((2, Evm::Log { l: 0, data: vec![] })),
+        Evm::decode(&[0xf0, 0x00]),
+        "Failed to decode instruction with empty payload"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Evm::decode(&[0x7f, 0x00, 0x00]),
+        "Truncated payload should be reported as unexpected EOF"
+    );
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Blob {
+    #[opcode = "0x01"]
+    Data {
+        #[varint = "uleb128"]
+        n: u64,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn decoding_hostile_payload_lengths() {
+    let mut mem = vec![0x01];
+    mem.extend(&[0xff; 9]);
+    mem.push(0x01);
+    mem.extend(&[0xab; 4]);
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Blob::decode(&mem),
+        "Payload length close to usize::MAX should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Blob::instruction_len(&mem),
+        "Payload length close to usize::MAX should be reported by instruction_len"
+    );
+}
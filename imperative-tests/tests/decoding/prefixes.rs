@@ -0,0 +1,227 @@
+use imperative_rs::{DecodeError, EncodeError, InstructionSet, Prefixed};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum CbPage {
+    #[opcode = "0b00000rrr"]
+    Rlc { r: u8 },
+    #[opcode = "0b01nnnrrr"]
+    Bit { n: u8, r: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum DdPage {
+    #[opcode = "0x21_nnnn"]
+    LdIx { n: u16 },
+    #[opcode = "0xcb_ii"]
+    Cb {
+        i: i8,
+        #[operand]
+        op: CbPage,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Z80 {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0xcb"]
+    Cb {
+        #[operand]
+        op: CbPage,
+    },
+    #[opcode = "0xdd"]
+    Dd {
+        #[operand]
+        op: DdPage,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Prefix {
+    #[opcode = "0x66"]
+    OperandSize,
+    #[opcode = "0xf3"]
+    Rep,
+    #[opcode = "0b0100wrxq"]
+    Rex {
+        w: bool,
+        r: bool,
+        x: bool,
+        #[variable = "q"]
+        b: bool,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Bundle {
+    #[opcode = "0x0f"]
+    Single {
+        #[operand]
+        op: Prefixed<Prefix, Z80>,
+    },
+    #[opcode = "0x1f"]
+    Pair {
+        #[operand]
+        first: Prefixed<Prefix, Z80>,
+        #[operand]
+        second: Prefixed<Prefix, Z80>,
+    },
+}
+
+#[test]
+fn decoding_opcode_pages() {
+    assert_eq!(
+        Ok((
+            2,
+            Z80::Cb {
+                op: CbPage::Bit { n: 3, r: 1 }
+            }
+        )),
+        Z80::decode(&[0xcb, 0b01_011_001]),
+        "Failed to decode instruction of opcode page"
+    );
+    assert_eq!(
+        Ok((
+            4,
+            Z80::Dd {
+                op: DdPage::LdIx { n: 0x1234 }
+            }
+        )),
+        Z80::decode(&[0xdd, 0x21, 0x12, 0x34]),
+        "Failed to decode instruction of second opcode page"
+    );
+    assert_eq!(
+        Ok((
+            4,
+            Z80::Dd {
+                op: DdPage::Cb {
+                    i: -2,
+                    op: CbPage::Rlc { r: 6 }
+                }
+            }
+        )),
+        Z80::decode(&[0xdd, 0xcb, 0xfe, 0x06]),
+        "Failed to decode instruction with displacement between prefixes and opcode"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Z80::decode(&[0xdd, 0xcb, 0xfe]),
+        "Missing opcode after prefixes should be reported as unexpected EOF"
+    );
+}
+
+#[test]
+fn decoding_prefixed() {
+    let (num_bytes, instr) = Prefixed::<Prefix, Z80>::decode(&[0x00]).unwrap();
+    assert_eq!(
+        1, num_bytes,
+        "Reported wrong number of bytes without prefix"
+    );
+    assert!(instr.prefixes.is_empty(), "Decoded prefix that isn't there");
+    assert_eq!(Z80::Nop, instr.instruction, "Decoded wrong instruction");
+
+    let (num_bytes, instr) =
+        Prefixed::<Prefix, Z80>::decode(&[0x66, 0xf3, 0b0100_1001, 0xcb, 0x02]).unwrap();
+    assert_eq!(5, num_bytes, "Reported wrong number of bytes with prefixes");
+    assert_eq!(
+        vec![
+            Prefix::OperandSize,
+            Prefix::Rep,
+            Prefix::Rex {
+                w: true,
+                r: false,
+                x: false,
+                b: true
+            }
+        ],
+        instr.prefixes,
+        "Decoded prefixes incorrectly"
+    );
+    assert_eq!(
+        Z80::Cb {
+            op: CbPage::Rlc { r: 2 }
+        },
+        instr.instruction,
+        "Decoded prefixed instruction incorrectly"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Prefixed::<Prefix, Z80>::decode(&[0x66, 0x66]),
+        "Missing instruction after prefixes should be reported as unexpected EOF"
+    );
+}
+
+#[test]
+fn prefixed_operand_lengths() {
+    assert_eq!(1, Prefixed::<Prefix, Z80>::MIN_LEN);
+    assert_eq!(None, Prefixed::<Prefix, Z80>::MAX_LEN);
+    assert_eq!(2, Bundle::MIN_LEN);
+    assert_eq!(
+        None,
+        Bundle::MAX_LEN,
+        "The number of prefixes is not bounded"
+    );
+    assert_eq!(
+        Ok(4),
+        Prefixed::<Prefix, Z80>::instruction_len(&[0x66, 0xf3, 0xcb, 0x02]),
+        "Failed to find length of prefixed instruction"
+    );
+    assert_eq!(
+        Ok(6),
+        Bundle::instruction_len(&[0x1f, 0x66, 0xcb, 0x02, 0xf3, 0x00]),
+        "Failed to find length of prefixed operands"
+    );
+    let mem = [0x1f, 0x66, 0xcb, 0x02, 0xf3, 0x00];
+    let (num_bytes, instr) = Bundle::decode(&mem).unwrap();
+    assert_eq!(6, num_bytes);
+    assert_eq!(
+        Bundle::Pair {
+            first: Prefixed {
+                prefixes: vec![Prefix::OperandSize],
+                instruction: Z80::Cb {
+                    op: CbPage::Rlc { r: 2 }
+                },
+            },
+            second: Prefixed {
+                prefixes: vec![Prefix::Rep],
+                instruction: Z80::Nop,
+            },
+        },
+        instr
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Bundle::instruction_len(&[0x0f, 0x66, 0x66]),
+        "Missing instruction after prefixes should be reported as unexpected EOF"
+    );
+}
+
+/// A prefix set that matches any memory without consuming a byte.
+#[derive(Debug, PartialEq)]
+struct Empty;
+
+impl InstructionSet for Empty {
+    fn decode(_mem: &[u8]) -> Result<(usize, Self), DecodeError> {
+        Ok((0, Empty))
+    }
+
+    fn encode(&self, _buf: &mut [u8]) -> Result<usize, EncodeError> {
+        Ok(0)
+    }
+}
+
+#[test]
+fn decoding_empty_prefixes() {
+    assert_eq!(
+        Ok((
+            1,
+            Prefixed {
+                prefixes: vec![],
+                instruction: Z80::Nop
+            }
+        )),
+        Prefixed::<Empty, Z80>::decode(&[0x00]),
+        "Prefixes without bytes should end the prefixes"
+    );
+    assert_eq!(Ok(1), Prefixed::<Empty, Z80>::instruction_len(&[0x00]));
+}
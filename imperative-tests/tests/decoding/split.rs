@@ -0,0 +1,98 @@
+use imperative_rs::{DecodeError, InstructionSet};
+use std::collections::VecDeque;
+
+#[derive(InstructionSet, Debug, PartialEq, Clone)]
+enum Isa {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x01_00_xx_xx"]
+    Jmp { x: u16 },
+    #[opcode = "0x02_nn"]
+    Data {
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn decoding_split_instructions() {
+    let code = [0x01, 0x00, 0x12, 0x34];
+    for split in 0..=code.len() {
+        let (first, second) = code.split_at(split);
+        assert_eq!(
+            Ok((4, Isa::Jmp { x: 0x1234 })),
+            Isa::decode_split(first, second),
+            "Failed to decode instruction split after {} bytes",
+            split
+        );
+    }
+    assert_eq!(
+        Ok((1, Isa::Nop)),
+        Isa::decode_split(&[0x00, 0x01], &[0x00]),
+        "Instruction within the first slice"
+    );
+}
+
+#[test]
+fn decoding_split_payloads() {
+    let mut code = vec![0x02, 50];
+    code.extend(0..50);
+    let (first, second) = code.split_at(20);
+    assert_eq!(
+        Ok((
+            52,
+            Isa::Data {
+                n: 50,
+                data: (0..50).collect()
+            }
+        )),
+        Isa::decode_split(first, second),
+        "Failed to decode payload longer than the stack buffer"
+    );
+}
+
+#[test]
+fn decoding_split_errors() {
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_split(&[0x01, 0x00], &[0x12]),
+        "Truncated instruction should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode_split(&[], &[]),
+        "Empty memory should be reported"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Isa::decode_split(&[0x01], &[0x01, 0x00, 0x00]),
+        "Unknown opcode across the split should be reported"
+    );
+}
+
+#[test]
+fn decoding_ring_buffer() {
+    let instructions = vec![
+        Isa::Jmp { x: 0xbeef },
+        Isa::Nop,
+        Isa::Data {
+            n: 3,
+            data: vec![7, 8, 9],
+        },
+        Isa::Jmp { x: 0x0102 },
+        Isa::Nop,
+    ];
+    let mut ring = VecDeque::with_capacity(8);
+    let mut decoded = vec![];
+    for instr in &instructions {
+        let mut buf = [0u8; 8];
+        let num_bytes = instr.encode(&mut buf).unwrap();
+        ring.extend(&buf[..num_bytes]);
+        let (first, second) = ring.as_slices();
+        let (num_bytes, instr) = Isa::decode_split(first, second).unwrap();
+        ring.drain(..num_bytes);
+        decoded.push(instr);
+    }
+    assert_eq!(instructions, decoded);
+}
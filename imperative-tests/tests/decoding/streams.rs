@@ -0,0 +1,224 @@
+use bytes::{Buf, Bytes};
+use imperative_rs::{buf, io, DecodeError, InstructionSet};
+use std::collections::VecDeque;
+use std::io::{ErrorKind, IoSlice, Read};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Isa {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x01_00_xx_xx"]
+    Jmp { x: u16 },
+    #[opcode = "0x02"]
+    Push {
+        #[varint = "uleb128"]
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+/// A reader that hands out at most one byte per call, like a slow socket.
+struct Trickle<'m>(&'m [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+/// A buf of many small chunks. `chunks_vectored(..)` is only implemented if `vectored` is set.
+#[derive(Clone)]
+struct Fragments {
+    chunks: VecDeque<Vec<u8>>,
+    vectored: bool,
+}
+
+impl Fragments {
+    fn new(mem: &[u8], chunk_len: usize, vectored: bool) -> Fragments {
+        Fragments {
+            chunks: mem.chunks(chunk_len).map(|chunk| chunk.to_vec()).collect(),
+            vectored,
+        }
+    }
+
+    fn from_chunks(chunks: &[&[u8]], vectored: bool) -> Fragments {
+        Fragments {
+            chunks: chunks.iter().map(|chunk| chunk.to_vec()).collect(),
+            vectored,
+        }
+    }
+}
+
+impl Buf for Fragments {
+    fn remaining(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.chunks.front().map_or(&[], |chunk| &chunk[..])
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let front = self.chunks.front_mut().unwrap();
+            if cnt < front.len() {
+                front.drain(..cnt);
+                return;
+            }
+            cnt -= front.len();
+            self.chunks.pop_front();
+        }
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        //like the default, which only exposes the first chunk
+        let num_chunks = if self.vectored { self.chunks.len() } else { 1 };
+        let mut filled = 0;
+        for (slice, chunk) in dst.iter_mut().zip(self.chunks.iter().take(num_chunks)) {
+            *slice = IoSlice::new(chunk);
+            filled += 1;
+        }
+        filled
+    }
+}
+
+const CODE: [u8; 9] = [0x01, 0x00, 0xab, 0xcd, 0x00, 0x02, 0x02, 0x11, 0x22];
+
+#[test]
+fn truncated_opcodes_are_unexpected_eof() {
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Isa::decode(&[0x01, 0x00]),
+        "Truncated opcode should be reported as unexpected EOF"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Isa::decode(&[0x01, 0x01]),
+        "Mismatching truncated opcode should be reported as unknown"
+    );
+}
+
+#[test]
+fn reading_instructions() {
+    let mut reader = Trickle(&CODE);
+    assert_eq!(
+        Some((4, Isa::Jmp { x: 0xabcd })),
+        io::read_instruction(&mut reader).unwrap(),
+    );
+    assert_eq!(
+        Some((1, Isa::Nop)),
+        io::read_instruction(&mut reader).unwrap()
+    );
+    assert_eq!(
+        Some((
+            4,
+            Isa::Push {
+                n: 2,
+                data: vec![0x11, 0x22]
+            }
+        )),
+        io::read_instruction(&mut reader).unwrap(),
+    );
+    assert_eq!(
+        None,
+        io::read_instruction::<Isa, _>(&mut reader).unwrap(),
+        "End of the reader should be reported as None"
+    );
+}
+
+#[test]
+fn reading_leaves_following_bytes() {
+    let mut reader = &CODE[..];
+    io::read_instruction::<Isa, _>(&mut reader).unwrap();
+    assert_eq!(&CODE[4..], reader, "Following instruction was consumed");
+}
+
+#[test]
+fn reading_errors() {
+    let err = io::read_instruction::<Isa, _>(&mut &CODE[..3]).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    let err = io::read_instruction::<Isa, _>(&mut &[0x03u8][..]).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(
+        Some(&DecodeError::UnknownOpcode),
+        err.get_ref().and_then(|inner| inner.downcast_ref()),
+    );
+}
+
+#[test]
+fn decoding_from_buf() {
+    let mut mem = Bytes::from_static(&CODE);
+    assert_eq!(Ok((4, Isa::Jmp { x: 0xabcd })), buf::decode_buf(&mut mem));
+    assert_eq!(Ok((1, Isa::Nop)), buf::decode_buf(&mut mem));
+    assert_eq!(4, mem.remaining());
+}
+
+#[test]
+fn decoding_from_chunked_buf() {
+    for vectored in [true, false].iter() {
+        let mut mem = Fragments::from_chunks(&[&CODE[..2], &CODE[2..6]], *vectored);
+        assert_eq!(Ok((4, Isa::Jmp { x: 0xabcd })), buf::decode_buf(&mut mem));
+        assert_eq!(Ok((1, Isa::Nop)), buf::decode_buf(&mut mem));
+        assert_eq!(
+            Err(DecodeError::UnexpectedEOF),
+            buf::decode_buf::<Isa, _>(&mut mem)
+        );
+        assert_eq!(1, mem.remaining(), "Incomplete instruction was consumed");
+    }
+}
+
+#[test]
+fn decoding_after_more_chunks_arrived() {
+    for vectored in [true, false].iter() {
+        let mut mem = Fragments::from_chunks(&[&[0x01], &[0x00]], *vectored);
+        assert_eq!(
+            Err(DecodeError::UnexpectedEOF),
+            buf::decode_buf::<Isa, _>(&mut mem)
+        );
+        assert_eq!(2, mem.remaining(), "Incomplete instruction was consumed");
+        mem.chunks.push_back(vec![0xab, 0xcd]);
+        assert_eq!(
+            Ok((4, Isa::Jmp { x: 0xabcd })),
+            buf::decode_buf(&mut mem),
+            "Failed to decode instruction once its last chunk arrived"
+        );
+        assert_eq!(0, mem.remaining());
+    }
+}
+
+#[test]
+fn decoding_from_many_chunks() {
+    let mut mem = vec![0x02, 100];
+    mem.extend(0..100);
+    mem.push(0x00);
+    for vectored in [true, false].iter() {
+        let mut frags = Fragments::new(&mem, 1, *vectored);
+        assert_eq!(
+            Ok((
+                102,
+                Isa::Push {
+                    n: 100,
+                    data: (0..100).collect()
+                }
+            )),
+            buf::decode_buf(&mut frags),
+            "Failed to decode instruction spanning 102 chunks"
+        );
+        assert_eq!(1, frags.remaining(), "Following instruction was consumed");
+        assert_eq!(Ok((1, Isa::Nop)), buf::decode_buf(&mut frags));
+    }
+    for vectored in [true, false].iter() {
+        let mut frags = Fragments::new(&mem[..80], 1, *vectored);
+        assert_eq!(
+            Err(DecodeError::UnexpectedEOF),
+            buf::decode_buf::<Isa, _>(&mut frags)
+        );
+        assert_eq!(80, frags.remaining(), "Incomplete instruction was consumed");
+    }
+}
@@ -0,0 +1,205 @@
+use imperative_rs::{InstructionSet, WordInstructionSet};
+
+macro_rules! chip8 {
+    ($(#[$attr:meta])*) => {
+        #[derive(InstructionSet, Debug, PartialEq)]
+        #[imperative(word = "u16")]
+        $(#[$attr])*
+        pub enum Chip8 {
+            #[opcode = "0x00cn"]
+            ScDown { n: u8 },
+            #[opcode = "0x00e0"]
+            Cls,
+            #[opcode = "0x00ee"]
+            Rts,
+            #[opcode = "0x1nnn"]
+            Jmp { n: u16 },
+            #[opcode = "0x3xrr"]
+            SkEq { x: u8, r: u8 },
+            #[opcode = "0x5xy0"]
+            SkEq2 { x: u8, y: u8 },
+            #[opcode = "0x8xy0"]
+            Mov2 { x: u8, y: u8 },
+            #[opcode = "0x8xy1"]
+            Or { x: u8, y: u8 },
+            #[opcode = "0x8x06"]
+            Shr { x: u8 },
+            #[opcode = "0x8x0e"]
+            Shl { x: u8 },
+            #[opcode = "0xdxyn"]
+            Sprite { x: u8, y: u8, n: u8 },
+            #[opcode = "0xek9e"]
+            SkPr { k: u8 },
+            #[opcode = "0xeka1"]
+            SkUp { k: u8 },
+            #[opcode = "0xfr07"]
+            GDelay { r: u8 },
+            #[opcode = "0xfr65"]
+            Ldr { r: u8 },
+        }
+    };
+}
+
+macro_rules! mixed {
+    ($(#[$attr:meta])*) => {
+        #[derive(InstructionSet, Debug, PartialEq)]
+        $(#[$attr])*
+        pub enum Mixed {
+            #[opcode = "0x0x"]
+            Short { x: u8 },
+            #[opcode = "0x1x_yy"]
+            Long { x: u8, y: u8 },
+            #[opcode = "0x2f_yy_zz"]
+            Longer { y: u8, z: u8 },
+            #[opcode = "0b1100xx00"]
+            P { x: u8 },
+            #[opcode = "0b110000x1"]
+            Q { x: bool },
+            #[opcode = "0b1100111x"]
+            R { x: bool },
+            #[opcode = "0b01******"]
+            Ignored,
+        }
+    };
+}
+
+//every bit of the byte is variable in some instruction, so tables need to match on fields that
+//are variable in some instructions
+macro_rules! fields {
+    ($(#[$attr:meta])*) => {
+        #[derive(InstructionSet, Debug, PartialEq)]
+        $(#[$attr])*
+        pub enum Fields {
+            #[opcode = "0b00xx_0001"]
+            Ld { x: u8 },
+            #[opcode = "0bxx00_0010"]
+            St { x: u8 },
+            #[opcode = "0b01xx_xx11"]
+            Mv { x: u8 },
+            #[opcode = "0b1xxx_0100"]
+            Jp { x: u8 },
+            #[opcode = "0bxxxx_1000"]
+            Ex { x: u8 },
+            #[opcode = "0b1110_00xx"]
+            Wd { x: u8 },
+        }
+    };
+}
+
+mod tree {
+    use imperative_rs::InstructionSet;
+    chip8!();
+    mixed!();
+    fields!();
+}
+
+mod table {
+    use imperative_rs::InstructionSet;
+    chip8!(#[imperative(decoder = "table")]);
+    mixed!(#[imperative(decoder = "table")]);
+    fields!(#[imperative(decoder = "table")]);
+}
+
+mod size {
+    use imperative_rs::InstructionSet;
+    chip8!(#[imperative(optimize = "size")]);
+    mixed!(#[imperative(optimize = "size")]);
+    fields!(#[imperative(decoder = "table", optimize = "size")]);
+}
+
+#[test]
+fn table_decoder_matches_tree_decoder() {
+    for first in 0..=255u8 {
+        for second in 0..=255u8 {
+            let mem = [first, second];
+            assert_eq!(
+                format!("{:?}", tree::Chip8::decode(&mem)),
+                format!("{:?}", table::Chip8::decode(&mem)),
+                "Decoders differ for {:x?}",
+                mem
+            );
+            assert_eq!(
+                format!("{:?}", tree::Mixed::decode(&mem)),
+                format!("{:?}", table::Mixed::decode(&mem)),
+                "Decoders differ for {:x?}",
+                mem
+            );
+        }
+        assert_eq!(
+            format!("{:?}", tree::Fields::decode(&[first])),
+            format!("{:?}", table::Fields::decode(&[first])),
+            "Decoders differ for {:x?}",
+            first
+        );
+        //truncated memories may be reported as unknown opcodes or as EOF
+        for mem in [vec![], vec![first], vec![first, 0x12, 0x34]].iter() {
+            assert_eq!(
+                format!("{:?}", tree::Chip8::decode(mem).ok()),
+                format!("{:?}", table::Chip8::decode(mem).ok()),
+                "Decoders differ for {:x?}",
+                mem
+            );
+            assert_eq!(
+                format!("{:?}", tree::Mixed::decode(mem).ok()),
+                format!("{:?}", table::Mixed::decode(mem).ok()),
+                "Decoders differ for {:x?}",
+                mem
+            );
+        }
+    }
+}
+
+#[test]
+fn size_optimized_decoder_matches_tree_decoder() {
+    for first in 0..=255u8 {
+        for second in 0..=255u8 {
+            let mem = [first, second];
+            assert_eq!(
+                format!("{:?}", tree::Chip8::decode(&mem)),
+                format!("{:?}", size::Chip8::decode(&mem)),
+                "Decoders differ for {:x?}",
+                mem
+            );
+            assert_eq!(
+                format!("{:?}", tree::Mixed::decode(&mem)),
+                format!("{:?}", size::Mixed::decode(&mem)),
+                "Decoders differ for {:x?}",
+                mem
+            );
+        }
+        assert_eq!(
+            format!("{:?}", tree::Fields::decode(&[first])),
+            format!("{:?}", size::Fields::decode(&[first])),
+            "Decoders differ for {:x?}",
+            first
+        );
+    }
+}
+
+#[test]
+fn table_decoder_words() {
+    for word in 0..=0xffffu16 {
+        assert_eq!(
+            format!("{:?}", tree::Chip8::decode_words(&[word])),
+            format!("{:?}", table::Chip8::decode_words(&[word])),
+            "Decoders differ for {:x?}",
+            word
+        );
+    }
+}
+
+#[test]
+fn table_decoder() {
+    assert_eq!(
+        Ok((2, table::Chip8::Sprite { x: 1, y: 2, n: 3 })),
+        table::Chip8::decode(&[0xd1, 0x23])
+    );
+    assert_eq!(
+        Ok((2, table::Mixed::Long { x: 5, y: 0xab })),
+        table::Mixed::decode(&[0x15, 0xab, 0x00])
+    );
+    assert_eq!(
+        Ok((1, table::Mixed::Q { x: true })),
+        table::Mixed::decode(&[0b1100_0011])
+    );
+}
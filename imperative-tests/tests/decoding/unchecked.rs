@@ -0,0 +1,67 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Mixed {
+    #[opcode = "0x0x"]
+    Short { x: u8 },
+    #[opcode = "0x1x_yy"]
+    Long { x: u8, y: u8 },
+    #[opcode = "0x2f_yy_zz"]
+    Longer { y: u8, z: u8 },
+    #[opcode = "0x3f"]
+    Data {
+        #[varint = "uleb128"]
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0b01******"]
+    Ignored,
+}
+
+#[test]
+fn decoding_padded() {
+    assert_eq!(3, Mixed::MAX_OPCODE_LEN);
+    for first in 0..=255u8 {
+        for second in 0..=255u8 {
+            let mem = [first, second, 0x5a];
+            assert_eq!(
+                Mixed::decode(&mem),
+                Mixed::decode_padded(&mem),
+                "Decoders differ for {:x?}",
+                mem
+            );
+        }
+    }
+}
+
+#[test]
+fn decoding_unchecked() {
+    let mem = [0x3f, 0x02, 0xab, 0xcd, 0x10];
+    assert_eq!(
+        Ok((
+            4,
+            Mixed::Data {
+                n: 2,
+                data: vec![0xab, 0xcd]
+            }
+        )),
+        unsafe { Mixed::decode_unchecked(&mem) }
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        unsafe { Mixed::decode_unchecked(&mem[..3]) },
+        "Trailing fields should still be checked"
+    );
+    assert_eq!(
+        Ok((2, Mixed::Long { x: 3, y: 0x11 })),
+        Mixed::decode_padded(&[0x13, 0x11, 0x00])
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "decode_unchecked needs at least 3 bytes but got 2")]
+fn decoding_unchecked_asserts_length() {
+    let _ = unsafe { Mixed::decode_unchecked(&[0x2f, 0x00]) };
+}
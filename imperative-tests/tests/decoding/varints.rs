@@ -0,0 +1,98 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Wasm {
+    #[opcode = "0x0c"]
+    Br {
+        #[varint = "uleb128"]
+        label: u32,
+    },
+    #[opcode = "0x41"]
+    I32Const {
+        #[varint = "sleb128"]
+        value: i32,
+    },
+    #[opcode = "0x42"]
+    SInt {
+        #[varint = "zigzag"]
+        value: i8,
+    },
+}
+
+#[test]
+fn decoding_varints() {
+    assert_eq!(
+        Ok((2, Wasm::Br { label: 0 })),
+        Wasm::decode(&[0x0c, 0x00]),
+        "Failed to decode one byte uleb128"
+    );
+    assert_eq!(
+        Ok((4, Wasm::Br { label: 624485 })),
+        Wasm::decode(&[0x0c, 0xe5, 0x8e, 0x26]),
+        "Failed to decode multi byte uleb128"
+    );
+    assert_eq!(
+        Ok((3, Wasm::I32Const { value: 7616 })),
+        Wasm::decode(&[0x41, 0xc0, 0x3b]),
+        "Failed to decode positive multi byte sleb128"
+    );
+    assert_eq!(
+        Ok((4, Wasm::I32Const { value: -123456 })),
+        Wasm::decode(&[0x41, 0xc0, 0xbb, 0x78]),
+        "Failed to decode negative sleb128"
+    );
+    assert_eq!(
+        Ok((2, Wasm::I32Const { value: 63 })),
+        Wasm::decode(&[0x41, 0x3f]),
+        "Failed to decode positive sleb128"
+    );
+    assert_eq!(
+        Ok((2, Wasm::SInt { value: -1 })),
+        Wasm::decode(&[0x42, 0x01]),
+        "Failed to decode zigzag"
+    );
+    assert_eq!(
+        Ok((3, Wasm::SInt { value: -128 })),
+        Wasm::decode(&[0x42, 0xff, 0x01]),
+        "Failed to decode zigzag"
+    );
+}
+
+#[test]
+fn decoding_invalid_varints() {
+    assert_eq!(
+        Err(DecodeError::Overlong),
+        Wasm::decode(&[0x0c, 0x80, 0x00]),
+        "Overlong uleb128 should be rejected"
+    );
+    assert_eq!(
+        Err(DecodeError::Overlong),
+        Wasm::decode(&[0x41, 0xff, 0x7f]),
+        "Overlong sleb128 should be rejected"
+    );
+    assert_eq!(
+        Err(DecodeError::Overlong),
+        Wasm::decode(&[0x41, 0x80, 0x00]),
+        "Overlong sleb128 should be rejected"
+    );
+    assert_eq!(
+        Ok((3, Wasm::I32Const { value: 64 })),
+        Wasm::decode(&[0x41, 0xc0, 0x00]),
+        "Sign byte of positive sleb128 is not overlong"
+    );
+    assert_eq!(
+        Err(DecodeError::Overflow),
+        Wasm::decode(&[0x0c, 0x80, 0x80, 0x80, 0x80, 0x10]),
+        "uleb128 larger than the field should overflow"
+    );
+    assert_eq!(
+        Err(DecodeError::Overflow),
+        Wasm::decode(&[0x42, 0x80, 0x02]),
+        "zigzag larger than the field should overflow"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Wasm::decode(&[0x0c, 0x80, 0x80]),
+        "Truncated varint should be reported as unexpected EOF"
+    );
+}
@@ -0,0 +1,8 @@
+# instruction, executions
+Mov, 1500
+Add, 900
+Jmp 300
+SkEq 20
+Sprite 5
+Cls 0
+Rts 0
@@ -0,0 +1,123 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Plain {
+    #[opcode = "0x00e0"]
+    Cls,
+    #[opcode = "0x00ee"]
+    Rts,
+    #[opcode = "0x1nnn"]
+    Jmp { n: u16 },
+    #[opcode = "0x3xrr"]
+    SkEq { x: u8, r: u8 },
+    #[opcode = "0x6xrr"]
+    Mov { x: u8, r: u8 },
+    #[opcode = "0x7xrr"]
+    Add { x: u8, r: u8 },
+    #[opcode = "0x8xy0"]
+    Mov2 { x: u8, y: u8 },
+    #[opcode = "0x8xy4"]
+    Add2 { x: u8, y: u8 },
+    #[opcode = "0xdxyn"]
+    Sprite { x: u8, y: u8, n: u8 },
+    #[opcode = "0xfr65"]
+    Ldr { r: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Weighted {
+    #[opcode = "0x00e0"]
+    #[weight = 0]
+    Cls,
+    #[opcode = "0x00ee"]
+    #[weight = 0]
+    Rts,
+    #[opcode = "0x1nnn"]
+    #[weight = 300]
+    Jmp { n: u16 },
+    #[opcode = "0x3xrr"]
+    #[weight = 20]
+    SkEq { x: u8, r: u8 },
+    #[opcode = "0x6xrr"]
+    #[weight = 1500]
+    Mov { x: u8, r: u8 },
+    #[opcode = "0x7xrr"]
+    #[weight = 900]
+    Add { x: u8, r: u8 },
+    #[opcode = "0x8xy0"]
+    Mov2 { x: u8, y: u8 },
+    #[opcode = "0x8xy4"]
+    Add2 { x: u8, y: u8 },
+    #[opcode = "0xdxyn"]
+    #[weight = 5]
+    Sprite { x: u8, y: u8, n: u8 },
+    #[opcode = "0xfr65"]
+    #[weight = 0]
+    Ldr { r: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(profile = "tests/decoding/weights.profile")]
+enum Profiled {
+    #[opcode = "0x00e0"]
+    Cls,
+    #[opcode = "0x00ee"]
+    Rts,
+    #[opcode = "0x1nnn"]
+    Jmp { n: u16 },
+    #[opcode = "0x3xrr"]
+    SkEq { x: u8, r: u8 },
+    #[opcode = "0x6xrr"]
+    Mov { x: u8, r: u8 },
+    #[opcode = "0x7xrr"]
+    #[weight = 1]
+    Add { x: u8, r: u8 },
+    #[opcode = "0x8xy0"]
+    Mov2 { x: u8, y: u8 },
+    #[opcode = "0x8xy4"]
+    Add2 { x: u8, y: u8 },
+    #[opcode = "0xdxyn"]
+    Sprite { x: u8, y: u8, n: u8 },
+    #[opcode = "0xfr65"]
+    Ldr { r: u8 },
+}
+
+#[test]
+fn weights_keep_decoded_instructions() {
+    for first in 0..=255u8 {
+        for second in 0..=255u8 {
+            let mem = [first, second];
+            let plain = format!("{:?}", Plain::decode(&mem));
+            assert_eq!(
+                plain.replace("Plain", "Weighted"),
+                format!("{:?}", Weighted::decode(&mem)),
+                "Weighted decoder differs for {:x?}",
+                mem
+            );
+            assert_eq!(
+                plain.replace("Plain", "Profiled"),
+                format!("{:?}", Profiled::decode(&mem)),
+                "Profiled decoder differs for {:x?}",
+                mem
+            );
+        }
+        assert_eq!(
+            Plain::decode(&[first]).ok().is_some(),
+            Weighted::decode(&[first]).ok().is_some(),
+            "Weighted decoder differs for truncated memory"
+        );
+    }
+}
+
+#[test]
+fn weighted_decoding() {
+    assert_eq!(
+        Ok((2, Weighted::Mov { x: 3, r: 0x42 })),
+        Weighted::decode(&[0x63, 0x42])
+    );
+    assert_eq!(
+        Ok((2, Profiled::Add2 { x: 1, y: 2 })),
+        Profiled::decode(&[0x81, 0x24])
+    );
+    assert_eq!(Ok((2, Profiled::Cls)), Profiled::decode(&[0x00, 0xe0]));
+}
@@ -0,0 +1,84 @@
+use imperative_rs::{DecodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Rv {
+    #[opcode = "0bmmmmmmmmmmmm_sssss_000_rrrrr_0010011"]
+    Addi { m: u16, s: u8, r: u8 },
+    #[opcode = "0b0000000_ttttt_sssss_000_rrrrr_0110011"]
+    Add { t: u8, s: u8, r: u8 },
+    #[opcode = "0bjjjjjjjjjjjjjjjjjjjj_rrrrr_1101111"]
+    Jal { j: u32, r: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Wide {
+    #[opcode = "0x0fxxxx"]
+    Three { x: u16 },
+    #[opcode = "0x1f_xxxxxxxxxxxxxxxx_ff"]
+    Ten { x: u64 },
+    #[opcode = "0b00101111_q000_0000"]
+    Flag { q: bool },
+    #[opcode = "0x3f_xx"]
+    Signed { x: i8 },
+}
+
+#[test]
+fn decoding_32_bit_opcodes() {
+    assert_eq!(
+        Ok((
+            4,
+            Rv::Addi {
+                m: 0x7ff,
+                s: 5,
+                r: 10
+            }
+        )),
+        Rv::decode(&[0x7f, 0xf2, 0x85, 0x13]),
+        "Failed to decode fields spanning bytes"
+    );
+    assert_eq!(
+        Ok((4, Rv::Add { t: 3, s: 2, r: 1 })),
+        Rv::decode(&[0x00, 0x31, 0x00, 0xb3])
+    );
+    assert_eq!(
+        Ok((4, Rv::Jal { j: 0x83ffc, r: 7 })),
+        Rv::decode(&[0x83, 0xff, 0xc3, 0xef])
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Rv::decode(&[0x00, 0x31, 0x10, 0xb3]),
+        "Constant bits in the middle of the opcode should be checked"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Rv::decode(&[0x7f, 0xf2, 0x85]),
+        "Memory shorter than all opcodes should be reported"
+    );
+}
+
+#[test]
+fn decoding_odd_sized_opcodes() {
+    assert_eq!(
+        Ok((3, Wide::Three { x: 0xabcd })),
+        Wide::decode(&[0x0f, 0xab, 0xcd, 0xff]),
+        "Failed to decode three byte opcode"
+    );
+    assert_eq!(
+        Ok((
+            10,
+            Wide::Ten {
+                x: 0x0102_0304_0506_0708
+            }
+        )),
+        Wide::decode(&[0x1f, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xff]),
+        "Failed to decode opcode longer than eight bytes"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Wide::decode(&[0x1f, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0xfe]),
+        "Constant bits at the end of a long opcode should be checked"
+    );
+    assert_eq!(Ok((2, Wide::Flag { q: true })), Wide::decode(&[0x2f, 0x80]));
+    assert_eq!(Err(DecodeError::UnknownOpcode), Wide::decode(&[0x2f, 0x81]));
+    assert_eq!(Ok((2, Wide::Signed { x: -2 })), Wide::decode(&[0x3f, 0xfe]));
+}
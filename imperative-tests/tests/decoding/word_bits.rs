@@ -0,0 +1,109 @@
+use imperative_rs::{DecodeError, InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16", word_bits = 12)]
+enum Pdp8 {
+    #[opcode = "0b000_i_z_mmmmmmm"]
+    And { i: bool, z: bool, m: u8 },
+    #[opcode = "0b101_i_z_mmmmmmm"]
+    Jmp { i: bool, z: bool, m: u8 },
+    #[opcode = "0xf02"]
+    Hlt,
+    #[opcode = "0x600_nnn"]
+    Ldi { n: u16 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u64", word_bits = 36)]
+enum Pdp10 {
+    #[opcode = "0b010000000_rrrr_i_xxxx_yyyyyyyyyyyyyyyyyy"]
+    Move { r: u8, i: bool, x: u8, y: u32 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16", word_bits = 12)]
+enum Pdp8Iot {
+    #[opcode = "0x600_001"]
+    Skp,
+    #[opcode = "0x600_002"]
+    Clr,
+    #[opcode = "0x601_nnn"]
+    Ldi { n: u16 },
+}
+
+#[test]
+fn decoding_12_bit_words() {
+    assert_eq!(
+        Ok((
+            1,
+            Pdp8::And {
+                i: true,
+                z: false,
+                m: 0x45
+            }
+        )),
+        Pdp8::decode_words(&[0o0505u16]),
+        "Failed to decode 12 bit word"
+    );
+    assert_eq!(
+        Ok((1, Pdp8::Hlt)),
+        Pdp8::decode_words(&[0xff02u16]),
+        "Bits above the word width should be ignored"
+    );
+    assert_eq!(
+        Ok((2, Pdp8::Ldi { n: 0x123 })),
+        Pdp8::decode_words(&[0x600u16, 0x123]),
+        "Failed to decode instruction of two 12 bit words"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Pdp8::decode_words(&[0x601u16, 0x123]),
+        "The last nibble of a 12 bit opcode must not be padded"
+    );
+    assert_eq!(
+        Ok((4, Pdp8::Ldi { n: 0x123 })),
+        Pdp8::decode(&[0x06, 0x00, 0x01, 0x23]),
+        "Each 12 bit word should be stored in two bytes"
+    );
+}
+
+#[test]
+fn decoding_36_bit_words() {
+    assert_eq!(
+        Ok((
+            1,
+            Pdp10::Move {
+                r: 3,
+                i: false,
+                x: 1,
+                y: 0x1234
+            }
+        )),
+        Pdp10::decode_words(&[0x4_0184_1234_u64]),
+        "Failed to decode 36 bit word"
+    );
+}
+
+#[test]
+fn decoding_multiple_12_bit_words() {
+    assert_eq!(
+        Ok((2, Pdp8Iot::Clr)),
+        Pdp8Iot::decode_words(&[0x600u16, 0x002]),
+        "Failed to tell opcodes apart by their second word"
+    );
+    assert_eq!(
+        Ok((2, Pdp8Iot::Skp)),
+        Pdp8Iot::decode_words(&[0xf600u16, 0xf001]),
+        "Bits above the word width should be ignored in every word"
+    );
+    assert_eq!(
+        Ok((2, Pdp8Iot::Ldi { n: 0x123 })),
+        Pdp8Iot::decode_words(&[0xf601u16, 0x123]),
+        "Bits above the word width should not reach the next word"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Pdp8Iot::decode_words(&[0x600u16]),
+        "Memory shorter than all opcodes should be reported"
+    );
+}
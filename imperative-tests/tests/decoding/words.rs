@@ -0,0 +1,111 @@
+use imperative_rs::{DecodeError, InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Src {
+    #[opcode = "0x0000"]
+    Register,
+    #[opcode = "0b1_iiiiiiiiiiiiiii"]
+    Immediate { i: u16 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Pdp11 {
+    #[opcode = "0x0000"]
+    Halt,
+    #[opcode = "0b0001_sssss_rrrrrrr"]
+    Mov {
+        s: u8,
+        r: u8,
+        #[operand]
+        src: Src,
+    },
+    #[opcode = "0b0000_0001_oooo_oooo"]
+    Br { o: u8 },
+    #[opcode = "0x0002_xxxx"]
+    Long { x: u16 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u64")]
+enum Wide {
+    #[opcode = "0xf0_iiiiiiiiiiiiii"]
+    Load { i: u64 },
+    #[opcode = "0x0f_00000000_rrrrrr"]
+    Ret { r: u32 },
+}
+
+#[test]
+fn decoding_u16_words() {
+    assert_eq!(
+        Ok((1, Pdp11::Halt)),
+        Pdp11::decode_words(&[0x0000u16, 0xffff]),
+        "Failed to decode constant word"
+    );
+    assert_eq!(
+        Ok((1, Pdp11::Br { o: 0xab })),
+        Pdp11::decode_words(&[0x01abu16]),
+        "Failed to decode word with variable"
+    );
+    assert_eq!(
+        Ok((
+            2,
+            Pdp11::Mov {
+                s: 0x15,
+                r: 0x01,
+                src: Src::Immediate { i: 0x1234 }
+            }
+        )),
+        Pdp11::decode_words(&[0b0001_1010_1000_0001_u16, 0x9234]),
+        "Failed to decode word operand"
+    );
+    assert_eq!(
+        Ok((2, Pdp11::Long { x: 0xbeef })),
+        Pdp11::decode_words(&[0x0002u16, 0xbeef]),
+        "Failed to decode two word opcode"
+    );
+    assert_eq!(
+        Err(DecodeError::UnexpectedEOF),
+        Pdp11::decode_words(&[] as &[u16]),
+        "Empty memory should be reported as unexpected EOF"
+    );
+    assert_eq!(
+        Err(DecodeError::UnknownOpcode),
+        Pdp11::decode_words(&[0x0003u16]),
+        "Unknown opcode"
+    );
+    assert_eq!(
+        Pdp11::decode(&[0x01, 0xab]).map(|(_, instr)| instr),
+        Pdp11::decode_words(&[0x01abu16]).map(|(_, instr)| instr),
+        "Decoding words differs from decoding bytes"
+    );
+}
+
+#[test]
+fn decoding_u64_words() {
+    assert_eq!(
+        Ok((
+            1,
+            Wide::Load {
+                i: 0x0012_3456_789a_bcde
+            }
+        )),
+        Wide::decode_words(&[0xf012_3456_789a_bcdeu64]),
+        "Failed to decode u64 word"
+    );
+    assert_eq!(
+        Ok((1, Wide::Ret { r: 0xabcdef })),
+        Wide::decode_words(&[0x0f00_0000_00ab_cdefu64]),
+        "Failed to decode u64 word"
+    );
+}
+
+#[test]
+fn decoding_bytes_as_words() {
+    assert_eq!(
+        Ok((2, Src::Immediate { i: 0x1234 })),
+        <Src as WordInstructionSet<u8>>::decode_words(&[0x92, 0x34]),
+        "Every instruction set can be decoded from u8 words"
+    );
+}
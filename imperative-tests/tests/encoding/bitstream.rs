@@ -0,0 +1,133 @@
+use imperative_rs::{EncodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Reg {
+    #[opcode = "0b0xx"]
+    Low { x: u8 },
+    #[opcode = "0b1xxxx"]
+    High { x: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Packed {
+    #[opcode = "0b000"]
+    Nop,
+    #[opcode = "0b001xxxxx"]
+    Load { x: u8 },
+    #[opcode = "0b01xxxxxxxxxx"]
+    Jump { x: u16 },
+    #[opcode = "0b10"]
+    Mov {
+        #[operand]
+        dst: Reg,
+        #[operand]
+        src: Reg,
+    },
+    #[opcode = "0b110"]
+    Data {
+        #[varint = "uleb128"]
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+    #[opcode = "0b111"]
+    Halt,
+}
+
+#[test]
+fn encoding_bitstream() {
+    let instructions = [
+        Packed::Halt,
+        Packed::Jump { x: 0x2a5 },
+        Packed::Nop,
+        Packed::Load { x: 0b10110 },
+        Packed::Mov {
+            dst: Reg::Low { x: 2 },
+            src: Reg::High { x: 9 },
+        },
+        Packed::Halt,
+    ];
+    let mut buf = [0; 5];
+    let mut bit_offset = 0;
+    for instr in &instructions {
+        bit_offset += instr.encode_bits(&mut buf, bit_offset).unwrap();
+    }
+    assert_eq!(39, bit_offset, "Reported wrong number of bits");
+    assert_eq!(
+        [
+            0b1110_1101,
+            0b0100_1010,
+            0b0000_1101,
+            0b1010_0101,
+            0b1001_1110
+        ],
+        buf,
+        "Failed to encode unaligned instructions"
+    );
+    let mut bit_offset = 0;
+    for instr in &instructions {
+        let (num_bits, decoded) = Packed::decode_bits(&buf, bit_offset).unwrap();
+        assert_eq!(instr, &decoded, "Failed to decode encoded instruction");
+        bit_offset += num_bits;
+    }
+}
+
+#[test]
+fn encoding_bitstream_payloads() {
+    let mut buf = [0; 5];
+    let instr = Packed::Data {
+        n: 2,
+        data: vec![0xab, 0xcd],
+    };
+    assert_eq!(Ok(27), instr.encode_bits(&mut buf, 6));
+    assert_eq!(
+        [
+            0b0000_0011,
+            0b0000_0001,
+            0b0101_0101,
+            0b1110_0110,
+            0b1000_0000
+        ],
+        buf,
+        "Failed to encode unaligned varint and payload"
+    );
+    let instr = Packed::Data {
+        n: 2,
+        data: vec![0xab],
+    };
+    assert_eq!(
+        Err(EncodeError::InvalidLength),
+        instr.encode_bits(&mut buf, 6),
+        "Payload with wrong length should be rejected"
+    );
+}
+
+#[test]
+fn encoding_bitstream_keeps_other_bits() {
+    let mut buf = [0xff, 0xff];
+    assert_eq!(Ok(3), Packed::Nop.encode_bits(&mut buf, 2));
+    assert_eq!(
+        [0b1100_0111, 0xff],
+        buf,
+        "Bits outside of the instruction should be unchanged"
+    );
+    assert_eq!(Ok(12), Packed::Jump { x: 0 }.encode_bits(&mut buf, 4));
+    assert_eq!(
+        [0b1100_0100, 0b0000_0000],
+        buf,
+        "Failed to encode instruction spanning two bytes"
+    );
+}
+
+#[test]
+fn encoding_bitstream_eof() {
+    let mut buf = [0; 2];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        Packed::Jump { x: 1 }.encode_bits(&mut buf, 5),
+        "Instruction past the end of the buffer should be reported"
+    );
+    assert_eq!(Ok(12), Packed::Jump { x: 1 }.encode_bits(&mut buf, 4));
+}
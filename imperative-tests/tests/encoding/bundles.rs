@@ -0,0 +1,129 @@
+use imperative_rs::{EncodeError, InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Alu {
+    #[opcode = "0b0xxxyyy"]
+    Add { x: u8, y: u8 },
+    #[opcode = "0b1xxxxxx"]
+    Li { x: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Mem {
+    #[opcode = "0b00xxxxx"]
+    Load { x: u8 },
+    #[opcode = "0b01xxxxx"]
+    Store { x: u8 },
+    #[opcode = "0b1******"]
+    Nop,
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Bundle {
+    #[opcode = "0b00sssssssttttttt"]
+    AluAlu {
+        #[slot]
+        s: Alu,
+        #[slot]
+        t: Alu,
+    },
+    #[opcode = "0b01sssssssttttttt"]
+    AluMem {
+        #[slot]
+        s: Alu,
+        #[slot]
+        t: Mem,
+    },
+    #[opcode = "0b10sssssssttttttt"]
+    MemMem {
+        #[slot]
+        s: Mem,
+        #[slot]
+        t: Mem,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(bitstream)]
+enum Op {
+    #[opcode = "0b00xxxxxxxxxxxx"]
+    Imm { x: u16 },
+    #[opcode = "0b01xxxxxxyyyyyy"]
+    Add { x: u8, y: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Packet {
+    #[opcode = "0bsssssss_11_sssssss"]
+    One {
+        #[slot]
+        s: Op,
+    },
+    #[opcode = "0bsssssss_01_sssssss_ttttttt_11_ttttttt"]
+    Two {
+        #[slot]
+        s: Op,
+        #[slot]
+        t: Op,
+    },
+}
+
+#[test]
+fn encoding_bundles() {
+    let mut buf = [0; 2];
+    let bundle = Bundle::AluMem {
+        s: Alu::Add { x: 3, y: 5 },
+        t: Mem::Store { x: 9 },
+    };
+    assert_eq!(Ok(2), bundle.encode(&mut buf));
+    assert_eq!(
+        [0x4e, 0xa9],
+        buf,
+        "Failed to encode slots of different types"
+    );
+    let bundle = Bundle::MemMem {
+        s: Mem::Nop,
+        t: Mem::Load { x: 31 },
+    };
+    assert_eq!(Ok(2), bundle.encode(&mut buf));
+    assert_eq!(
+        [0xa0, 0x1f],
+        buf,
+        "Ignored bits of a slot should be encoded as zero"
+    );
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        bundle.encode(&mut buf[..1]),
+        "Too short buffer should be reported"
+    );
+}
+
+#[test]
+fn encoding_packets() {
+    let packet = Packet::Two {
+        s: Op::Add { x: 5, y: 9 },
+        t: Op::Imm { x: 0xabc },
+    };
+    let mut buf = [0; 4];
+    assert_eq!(Ok(4), packet.encode(&mut buf));
+    assert_eq!(
+        [0x44, 0xc9, 0x2b, 0xbc],
+        buf,
+        "Failed to encode slots split by parse bits"
+    );
+    let mut words = [0u16; 2];
+    assert_eq!(Ok(2), packet.encode_words(&mut words));
+    assert_eq!([0x44c9, 0x2bbc], words, "Failed to encode slots into words");
+    let packet = Packet::One {
+        s: Op::Imm { x: 1 },
+    };
+    assert_eq!(Ok(2), packet.encode(&mut buf));
+    assert_eq!(
+        Ok((2, packet)),
+        Packet::decode(&buf),
+        "Failed to decode encoded packet"
+    );
+}
@@ -0,0 +1,40 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum I8080 {
+    #[opcode = "0xc3_nnnn"]
+    Jmp {
+        #[endian = "little"]
+        n: u16,
+    },
+    #[opcode = "0xd_nnnnnn_0"]
+    Shifted {
+        #[endian = "little"]
+        n: u32,
+    },
+}
+
+#[test]
+fn encoding_little_endian() {
+    let cases = [
+        (I8080::Jmp { n: 0x1234 }, vec![0xc3, 0x34, 0x12]),
+        (I8080::Shifted { n: 0x123456 }, vec![0xd5, 0x63, 0x41, 0x20]),
+    ];
+    for (instr, correct) in cases.iter() {
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            Ok(correct.len()),
+            instr.encode(&mut buf),
+            "Failed to encode {:?}",
+            instr
+        );
+        assert_eq!(
+            correct[..],
+            buf[..correct.len()],
+            "Encoded {:?} as {:x?}. Correct: {:x?}",
+            instr,
+            buf,
+            correct
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use imperative_rs::{varint, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Reg {
+    #[opcode = "0x0r"]
+    Direct { r: u8 },
+    #[opcode = "0x1r"]
+    Indirect {
+        r: u8,
+        #[varint = "uleb128"]
+        offset: u16,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Isa {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x01_xx_xx"]
+    Jmp { x: u16 },
+    #[opcode = "0x02"]
+    Mov {
+        #[operand]
+        dst: Reg,
+        #[operand]
+        src: Reg,
+    },
+    #[opcode = "0x03"]
+    Imm {
+        #[varint = "sleb128"]
+        value: i32,
+    },
+    #[opcode = "0x04"]
+    Add {
+        #[varint = "zigzag"]
+        value: i64,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Data {
+    #[opcode = "0x0e"]
+    Table {
+        #[varint = "uleb128"]
+        count: u8,
+        #[len = "count"]
+        labels: Vec<u8>,
+    },
+}
+
+#[test]
+fn encoded_len_matches_encode() {
+    let instructions = [
+        Isa::Nop,
+        Isa::Jmp { x: 0x1234 },
+        Isa::Mov {
+            dst: Reg::Direct { r: 1 },
+            src: Reg::Indirect {
+                r: 2,
+                offset: 0x3fff,
+            },
+        },
+        Isa::Mov {
+            dst: Reg::Indirect { r: 3, offset: 0 },
+            src: Reg::Indirect {
+                r: 4,
+                offset: 0xffff,
+            },
+        },
+        Isa::Imm { value: 0 },
+        Isa::Imm { value: -65 },
+        Isa::Imm { value: i32::MIN },
+        Isa::Add { value: 63 },
+        Isa::Add { value: i64::MIN },
+    ];
+    let mut buf = [0; 16];
+    for instr in &instructions {
+        assert_eq!(
+            instr.encode(&mut buf),
+            Ok(instr.encoded_len()),
+            "Wrong length for {:?}",
+            instr
+        );
+    }
+    let table = Data::Table {
+        count: 3,
+        labels: vec![1, 2, 3],
+    };
+    assert_eq!(Ok(table.encoded_len()), table.encode(&mut buf));
+    assert_eq!(5, table.encoded_len());
+}
+
+#[test]
+fn encoding_to_bytes() {
+    assert_eq!(Some(11), Isa::MAX_LEN);
+    assert_eq!(11, Isa::MAX_ENCODED_LEN);
+    let (bytes, num_bytes) = Isa::Jmp { x: 0x1234 }.to_bytes().unwrap();
+    assert_eq!(3, num_bytes);
+    assert_eq!([0x01, 0x12, 0x34], bytes[..num_bytes]);
+    let instr = Isa::Mov {
+        dst: Reg::Direct { r: 1 },
+        src: Reg::Indirect { r: 2, offset: 300 },
+    };
+    let (bytes, num_bytes) = instr.to_bytes().unwrap();
+    assert_eq!(instr.encoded_len(), num_bytes);
+    assert_eq!(Ok((num_bytes, instr)), Isa::decode(&bytes));
+}
+
+#[test]
+fn encoding_to_vec() {
+    assert_eq!(Ok(vec![0x01, 0x12, 0x34]), Isa::Jmp { x: 0x1234 }.to_vec());
+    let table = Data::Table {
+        count: 3,
+        labels: vec![1, 2, 3],
+    };
+    assert_eq!(
+        Ok(5),
+        table.to_vec().map(|bytes| bytes.len()),
+        "Instructions with payloads should be encoded as well"
+    );
+    let table = Data::Table {
+        count: 2,
+        labels: vec![1, 2, 3],
+    };
+    assert!(
+        table.to_vec().is_err(),
+        "Encoding errors should be returned instead of panicking"
+    );
+}
+
+#[test]
+fn varint_lengths() {
+    let mut buf = [0; 19];
+    for value in [i128::MIN, i128::MAX].iter() {
+        assert_eq!(
+            varint::encode_sleb128(*value, &mut buf),
+            Ok(varint::sleb128_len(*value))
+        );
+        assert_eq!(
+            varint::encode_zigzag(*value, &mut buf),
+            Ok(varint::zigzag_len(*value))
+        );
+    }
+    for shift in 0..128 {
+        for value in [1u128 << shift, (1u128 << shift) - 1].iter() {
+            assert_eq!(
+                varint::encode_uleb128(*value, &mut buf),
+                Ok(varint::uleb128_len(*value))
+            );
+        }
+    }
+    for shift in 0..127 {
+        for value in [1i128 << shift, -(1i128 << shift), (1i128 << shift) - 1].iter() {
+            assert_eq!(
+                varint::encode_sleb128(*value, &mut buf),
+                Ok(varint::sleb128_len(*value))
+            );
+            assert_eq!(
+                varint::encode_zigzag(*value, &mut buf),
+                Ok(varint::zigzag_len(*value))
+            );
+        }
+    }
+}
@@ -1,4 +1,15 @@
 mod bin_opcodes;
+mod bitstream;
+mod bundles;
+mod endianness;
 mod hex_opcodes;
+mod lengths;
+mod operands;
+mod payloads;
+mod prefixes;
 mod star_opcodes;
+mod streams;
 mod variable_length;
+mod varints;
+mod word_bits;
+mod words;
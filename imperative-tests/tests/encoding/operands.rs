@@ -0,0 +1,70 @@
+use imperative_rs::{EncodeError, InstructionSet};
+
+#[derive(InstructionSet)]
+enum ModRm {
+    #[opcode = "0b11rrrmmm"]
+    Reg { r: u8, m: u8 },
+    #[opcode = "0b01rrrmmm_iiiiiiii"]
+    Disp { r: u8, m: u8, i: i8 },
+}
+
+#[derive(InstructionSet)]
+enum Is {
+    #[opcode = "0x02"]
+    Mov {
+        #[operand]
+        dst: ModRm,
+        #[operand]
+        src: ModRm,
+    },
+    #[opcode = "0x1x"]
+    Inc {
+        x: u8,
+        #[operand]
+        modrm: ModRm,
+    },
+}
+
+#[test]
+fn encoding_operands() {
+    let mut buf = [0u8; 4];
+    let mov = Is::Mov {
+        dst: ModRm::Disp { r: 3, m: 4, i: -1 },
+        src: ModRm::Reg { r: 1, m: 2 },
+    };
+    let correct = [0x02, 0b01_011_100, 0xff, 0b11_001_010];
+    assert_eq!(
+        Ok(4),
+        mov.encode(&mut buf),
+        "Failed to encode instruction with two operands"
+    );
+    assert_eq!(
+        correct, buf,
+        "Encoded instruction as {:x?}. Correct: {:x?}",
+        buf, correct
+    );
+
+    let mut buf = [0u8; 2];
+    let inc = Is::Inc {
+        x: 0xa,
+        modrm: ModRm::Reg { r: 7, m: 0 },
+    };
+    let correct = [0x1a, 0b11_111_000];
+    assert_eq!(
+        Ok(2),
+        inc.encode(&mut buf),
+        "Failed to encode instruction with variable and operand"
+    );
+    assert_eq!(
+        correct, buf,
+        "Encoded instruction as {:x?}. Correct: {:x?}",
+        buf, correct
+    );
+
+    let mut buf = [0u8; 3];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        mov.encode(&mut buf),
+        "Encoded operand into too short buffer"
+    );
+}
@@ -0,0 +1,49 @@
+use imperative_rs::{EncodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Evm {
+    #[opcode = "0b011nnnnn"]
+    Push {
+        n: u8,
+        #[len = "n + 1"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn encoding_payloads() {
+    let mut buf = [0u8; 3];
+    let instr = Evm::Push {
+        n: 1,
+        data: vec![0xab, 0xcd],
+    };
+    let correct = [0x61, 0xab, 0xcd];
+    assert_eq!(
+        Ok(3),
+        instr.encode(&mut buf),
+        "Failed to encode instruction with payload"
+    );
+    assert_eq!(
+        correct, buf,
+        "Encoded instruction as {:x?}. Correct: {:x?}",
+        buf, correct
+    );
+
+    let mut buf = [0u8; 2];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        instr.encode(&mut buf),
+        "Encoded payload into too short buffer"
+    );
+
+    let mut buf = [0u8; 3];
+    let instr = Evm::Push {
+        n: 0,
+        data: vec![0xab, 0xcd],
+    };
+    assert_eq!(
+        Err(EncodeError::InvalidLength),
+        instr.encode(&mut buf),
+        "Encoded payload whose length doesn't match"
+    );
+}
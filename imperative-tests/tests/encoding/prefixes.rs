@@ -0,0 +1,73 @@
+use imperative_rs::{InstructionSet, Prefixed};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum CbPage {
+    #[opcode = "0b00000rrr"]
+    Rlc { r: u8 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum DdPage {
+    #[opcode = "0xcb_ii"]
+    Cb {
+        i: i8,
+        #[operand]
+        op: CbPage,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Z80 {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0xdd"]
+    Dd {
+        #[operand]
+        op: DdPage,
+    },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Prefix {
+    #[opcode = "0x66"]
+    OperandSize,
+}
+
+#[test]
+fn encoding_prefixes() {
+    let mut buf = [0u8; 4];
+    let instr = Z80::Dd {
+        op: DdPage::Cb {
+            i: -2,
+            op: CbPage::Rlc { r: 6 },
+        },
+    };
+    let correct = [0xdd, 0xcb, 0xfe, 0x06];
+    assert_eq!(
+        Ok(4),
+        instr.encode(&mut buf),
+        "Failed to encode instruction of nested opcode pages"
+    );
+    assert_eq!(
+        correct, buf,
+        "Encoded instruction as {:x?}. Correct: {:x?}",
+        buf, correct
+    );
+
+    let mut buf = [0u8; 3];
+    let instr = Prefixed {
+        prefixes: vec![Prefix::OperandSize, Prefix::OperandSize],
+        instruction: Z80::Nop,
+    };
+    let correct = [0x66, 0x66, 0x00];
+    assert_eq!(
+        Ok(3),
+        instr.encode(&mut buf),
+        "Failed to encode prefixed instruction"
+    );
+    assert_eq!(
+        correct, buf,
+        "Encoded instruction as {:x?}. Correct: {:x?}",
+        buf, correct
+    );
+}
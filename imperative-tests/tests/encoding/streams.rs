@@ -0,0 +1,73 @@
+use bytes::BytesMut;
+use imperative_rs::{buf, io, EncodeError, InstructionSet};
+use std::io::ErrorKind;
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Isa {
+    #[opcode = "0x00"]
+    Nop,
+    #[opcode = "0x01_xx_xx"]
+    Jmp { x: u16 },
+    #[opcode = "0x02"]
+    Push {
+        #[varint = "uleb128"]
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+#[test]
+fn writing_instructions() {
+    let mut out = vec![];
+    assert_eq!(
+        3,
+        io::write_instruction(&Isa::Jmp { x: 0x1234 }, &mut out).unwrap()
+    );
+    assert_eq!(1, io::write_instruction(&Isa::Nop, &mut out).unwrap());
+    assert_eq!(vec![0x01, 0x12, 0x34, 0x00], out);
+}
+
+#[test]
+fn writing_long_instructions() {
+    let data: Vec<u8> = (0..100).collect();
+    let mut out = vec![];
+    let push = Isa::Push {
+        n: 100,
+        data: data.clone(),
+    };
+    assert_eq!(102, io::write_instruction(&push, &mut out).unwrap());
+    assert_eq!([0x02, 100], out[..2]);
+    assert_eq!(data, out[2..]);
+}
+
+#[test]
+fn writing_errors() {
+    let push = Isa::Push {
+        n: 3,
+        data: vec![0x00],
+    };
+    let mut out = vec![];
+    let err = io::write_instruction(&push, &mut out).unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+    assert!(out.is_empty(), "Nothing should be written on error");
+}
+
+#[test]
+fn encoding_into_buf() {
+    let mut out = BytesMut::new();
+    assert_eq!(Ok(3), buf::encode_buf(&Isa::Jmp { x: 0x1234 }, &mut out));
+    assert_eq!(Ok(1), buf::encode_buf(&Isa::Nop, &mut out));
+    assert_eq!(&[0x01, 0x12, 0x34, 0x00][..], &out[..]);
+}
+
+#[test]
+fn encoding_into_short_buf() {
+    let mut mem = [0u8; 2];
+    let mut out = &mut mem[..];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        buf::encode_buf(&Isa::Jmp { x: 0x1234 }, &mut out)
+    );
+    assert_eq!(2, out.len(), "Nothing should be written on error");
+}
@@ -0,0 +1,86 @@
+use imperative_rs::{EncodeError, InstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+enum Wasm {
+    #[opcode = "0x0c"]
+    Br {
+        #[varint = "uleb128"]
+        label: u32,
+    },
+    #[opcode = "0x41"]
+    I32Const {
+        #[varint = "sleb128"]
+        value: i32,
+    },
+    #[opcode = "0x42"]
+    SInt {
+        #[varint = "zigzag"]
+        value: i64,
+    },
+    #[opcode = "0x0e"]
+    BrTable {
+        #[varint = "uleb128"]
+        count: u8,
+        #[len = "count"]
+        labels: Vec<u8>,
+    },
+}
+
+#[test]
+fn encoding_varints() {
+    let cases = [
+        (Wasm::Br { label: 0 }, vec![0x0c, 0x00]),
+        (Wasm::Br { label: 624485 }, vec![0x0c, 0xe5, 0x8e, 0x26]),
+        (Wasm::I32Const { value: 64 }, vec![0x41, 0xc0, 0x00]),
+        (Wasm::I32Const { value: -64 }, vec![0x41, 0x40]),
+        (
+            Wasm::I32Const { value: -123456 },
+            vec![0x41, 0xc0, 0xbb, 0x78],
+        ),
+        (
+            Wasm::I32Const { value: i32::MIN },
+            vec![0x41, 0x80, 0x80, 0x80, 0x80, 0x78],
+        ),
+        (Wasm::SInt { value: -1 }, vec![0x42, 0x01]),
+        (Wasm::SInt { value: 64 }, vec![0x42, 0x80, 0x01]),
+        (
+            Wasm::BrTable {
+                count: 2,
+                labels: vec![0x00, 0x01],
+            },
+            vec![0x0e, 0x02, 0x00, 0x01],
+        ),
+    ];
+    for (instr, correct) in cases.iter() {
+        let mut buf = [0u8; 16];
+        assert_eq!(
+            Ok(correct.len()),
+            instr.encode(&mut buf),
+            "Failed to encode {:?}",
+            instr
+        );
+        assert_eq!(
+            correct[..],
+            buf[..correct.len()],
+            "Encoded {:?} as {:x?}. Correct: {:x?}",
+            instr,
+            buf,
+            correct
+        );
+        assert_eq!(
+            Ok((correct.len(), instr)),
+            Wasm::decode(&buf)
+                .as_ref()
+                .map(|(num_bytes, instr)| (*num_bytes, instr)),
+            "Encoded {:?} doesn't decode to itself",
+            instr
+        );
+    }
+
+    let mut buf = [0u8; 3];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        Wasm::Br { label: 624485 }.encode(&mut buf),
+        "Encoded varint into too short buffer"
+    );
+}
@@ -0,0 +1,47 @@
+use imperative_rs::{InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16", word_bits = 12)]
+enum Pdp8 {
+    #[opcode = "0b000_i_z_mmmmmmm"]
+    And { i: bool, z: bool, m: u8 },
+    #[opcode = "0x600_nnn"]
+    Ldi { n: u16 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u32", word_bits = 18)]
+enum Pdp1 {
+    #[opcode = "0b10000_i_mmmmmmmmmmmm"]
+    Lac { i: bool, m: u16 },
+}
+
+#[test]
+fn encoding_word_bits() {
+    let mut buf = [0u16; 2];
+    let instr = Pdp8::And {
+        i: true,
+        z: false,
+        m: 0x45,
+    };
+    assert_eq!(Ok(1), instr.encode_words(&mut buf));
+    assert_eq!(0o0505, buf[0], "Failed to encode 12 bit word");
+
+    assert_eq!(Ok(2), Pdp8::Ldi { n: 0x123 }.encode_words(&mut buf));
+    assert_eq!([0x600, 0x123], buf, "Failed to encode two 12 bit words");
+
+    let mut bytes = [0u8; 4];
+    assert_eq!(Ok(4), Pdp8::Ldi { n: 0x123 }.encode(&mut bytes));
+    assert_eq!(
+        [0x06, 0x00, 0x01, 0x23],
+        bytes,
+        "Each 12 bit word should be stored in two bytes"
+    );
+
+    let mut buf = [0u32; 1];
+    assert_eq!(
+        Ok(1),
+        Pdp1::Lac { i: true, m: 0o7777 }.encode_words(&mut buf)
+    );
+    assert_eq!([0o417777], buf, "Failed to encode 18 bit word");
+}
@@ -0,0 +1,80 @@
+use imperative_rs::{EncodeError, InstructionSet, WordInstructionSet};
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Src {
+    #[opcode = "0x0000"]
+    Register,
+    #[opcode = "0b1_iiiiiiiiiiiiiii"]
+    Immediate { i: u16 },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u16")]
+enum Pdp11 {
+    #[opcode = "0b0001_sssss_rrrrrrr"]
+    Mov {
+        s: u8,
+        r: u8,
+        #[operand]
+        src: Src,
+    },
+    #[opcode = "0x0002_xxxx"]
+    Long { x: u16 },
+    #[opcode = "0b0000_0011_0000_000z"]
+    Flag { z: bool },
+}
+
+#[derive(InstructionSet, Debug, PartialEq)]
+#[imperative(word = "u32")]
+enum Arm {
+    #[opcode = "0b1110_1010_oooooooooooooooooooooooo"]
+    B { o: u32 },
+}
+
+#[test]
+fn encoding_words() {
+    let cases = [
+        (
+            Pdp11::Mov {
+                s: 0x15,
+                r: 0x01,
+                src: Src::Immediate { i: 0x1234 },
+            },
+            vec![0b0001_1010_1000_0001, 0x9234],
+        ),
+        (Pdp11::Long { x: 0xbeef }, vec![0x0002, 0xbeef]),
+        (Pdp11::Flag { z: true }, vec![0x0301]),
+    ];
+    for (instr, correct) in cases.iter() {
+        let mut buf = [0u16; 2];
+        assert_eq!(
+            Ok(correct.len()),
+            instr.encode_words(&mut buf),
+            "Failed to encode {:?}",
+            instr
+        );
+        assert_eq!(
+            correct[..],
+            buf[..correct.len()],
+            "Encoded {:?} as {:x?}. Correct: {:x?}",
+            instr,
+            buf,
+            correct
+        );
+    }
+
+    let mut buf = [0u32; 1];
+    assert_eq!(Ok(1), Arm::B { o: 0x123456 }.encode_words(&mut buf));
+    assert_eq!([0xea12_3456], buf, "Failed to encode u32 word");
+
+    let mut buf = [0u16; 1];
+    assert_eq!(
+        Err(EncodeError::UnexpectedEOF),
+        Pdp11::Long { x: 0 }.encode_words(&mut buf),
+        "Encoded instruction into too short buffer"
+    );
+    let mut bytes = [0u8; 2];
+    assert_eq!(Ok(2), Pdp11::Flag { z: true }.encode(&mut bytes));
+    assert_eq!([0x03, 0x01], bytes, "Encoding bytes differs from words");
+}
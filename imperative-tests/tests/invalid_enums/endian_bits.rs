@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[opcode = "0x1nnn"]
+    A {
+        #[endian = "little"]
+        n: u16,
+    },
+}
+
+fn main() {}
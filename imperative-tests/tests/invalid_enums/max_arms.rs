@@ -0,0 +1,18 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+#[imperative(max_arms = 8)]
+enum Instructionset {
+    #[opcode = "0b0000_0000"]
+    A,
+    #[opcode = "0b0100_0000"]
+    B,
+    #[opcode = "0b1000_0000"]
+    C,
+    #[opcode = "0b1100_0000"]
+    D,
+    #[opcode = "0bxxxx_xxx1"]
+    E { x: u8 },
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[opcode = "0x0n"]
+    A {
+        n: u8,
+        #[len = "n"]
+        data: [u8; 4],
+    },
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+#[imperative(bitstream)]
+enum Op {
+    #[opcode = "0bxxxxxxxx"]
+    Imm { x: u8 },
+}
+
+#[derive(InstructionSet)]
+enum Bundle {
+    #[opcode = "0xss"]
+    One {
+        #[slot]
+        #[endian = "little"]
+        s: Op,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[opcode = "0x01"]
+    A {
+        #[varint = "uleb128"]
+        n: i32,
+    },
+}
+
+fn main() {}
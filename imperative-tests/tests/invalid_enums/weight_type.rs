@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[opcode = "0x00"]
+    #[weight = "hot"]
+    A,
+    #[opcode = "0x01"]
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+enum Instructionset {
+    #[opcode = "0x00"]
+    A,
+    #[opcode = "0x01"]
+    #[when = "ctx.thumb"]
+    B,
+}
+
+fn main() {}
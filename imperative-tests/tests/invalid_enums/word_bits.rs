@@ -0,0 +1,12 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+#[imperative(word = "u16", word_bits = 12)]
+enum Instructionset {
+    #[opcode = "0x123"]
+    A,
+    #[opcode = "0x45"]
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use imperative_rs::InstructionSet;
+
+#[derive(InstructionSet)]
+#[imperative(word = "u16")]
+enum Instructionset {
+    #[opcode = "0x01"]
+    A,
+    #[opcode = "0x02nn"]
+    B {
+        n: u8,
+        #[len = "n"]
+        data: Vec<u8>,
+    },
+}
+
+fn main() {}
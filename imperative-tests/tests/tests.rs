@@ -1,5 +1,4 @@
-use trybuild;
-
+mod cycles;
 mod decoding;
 mod encoding;
 
@@ -13,6 +12,9 @@ fn compile_tests() {
     t.compile_fail("tests/collisions/variable_suffix.rs");
     t.compile_fail("tests/collisions/mixed_bin_hex.rs");
     t.compile_fail("tests/collisions/star_opcodes.rs");
+    t.compile_fail("tests/collisions/same_mode.rs");
+    t.compile_fail("tests/collisions/bitstream_prefix.rs");
+    t.compile_fail("tests/collisions/large_set.rs");
 
     t.compile_fail("tests/variable_checks/missing_in_opcode.rs");
     t.compile_fail("tests/variable_checks/missing_field.rs");
@@ -22,6 +24,16 @@ fn compile_tests() {
     t.compile_fail("tests/overflows/overflows.rs");
     t.compile_fail("tests/invalid_enums/unnamed_fields.rs");
     t.compile_fail("tests/invalid_enums/var_name_longer_one.rs");
+    t.compile_fail("tests/invalid_enums/missing_cycles.rs");
+    t.compile_fail("tests/invalid_enums/when_without_context.rs");
+    t.compile_fail("tests/invalid_enums/payload_type.rs");
+    t.compile_fail("tests/invalid_enums/varint_type.rs");
+    t.compile_fail("tests/invalid_enums/endian_bits.rs");
+    t.compile_fail("tests/invalid_enums/word_size.rs");
+    t.compile_fail("tests/invalid_enums/slot_endian.rs");
+    t.compile_fail("tests/invalid_enums/word_bits.rs");
+    t.compile_fail("tests/invalid_enums/weight_type.rs");
+    t.compile_fail("tests/invalid_enums/max_arms.rs");
 
     //TODO: Improve macro hygiene. Macro should behave as expected, even if primitive type names
     //are redefined (e.g struct u8 {...})
@@ -0,0 +1,51 @@
+//! Helpers to read and write bit streams as used by the generated `decode_bits(..)` and
+//! `encode_bits(..)`. Bits are counted from the most significant bit of the first byte, i.e. bit
+//! offset 0 is `mem[0] & 0x80` and bit offset 9 is `mem[1] & 0x40`.
+use crate::EncodeError;
+
+/// Copies the bits of `mem` starting at `bit_offset` into `buf`. Bits beyond the end of `mem` are
+/// filled with zeros. Returns the number of bits that were copied from `mem`.
+pub fn read(mem: &[u8], bit_offset: usize, buf: &mut [u8]) -> usize {
+    let available = (8 * mem.len()).saturating_sub(bit_offset);
+    let (byte_idx, shift) = (bit_offset / 8, bit_offset % 8);
+    for (idx, byte) in buf.iter_mut().enumerate() {
+        let high = mem.get(byte_idx + idx).copied().unwrap_or(0);
+        let low = mem.get(byte_idx + idx + 1).copied().unwrap_or(0);
+        *byte = if shift == 0 {
+            high
+        } else {
+            (high << shift) | (low >> (8 - shift))
+        };
+    }
+    let copied = available.min(8 * buf.len());
+    //clear the bits that were not copied from mem
+    for (idx, byte) in buf.iter_mut().enumerate() {
+        let valid = copied.saturating_sub(8 * idx).min(8);
+        *byte &= !(0xffu8.checked_shr(valid as u32).unwrap_or(0));
+    }
+    copied
+}
+
+/// Writes the first `num_bits` bits of `src` into `buf` starting at `bit_offset`. All other bits
+/// of `buf` are left untouched.
+pub fn write(
+    buf: &mut [u8],
+    bit_offset: usize,
+    src: &[u8],
+    num_bits: usize,
+) -> Result<(), EncodeError> {
+    if 8 * buf.len() < bit_offset + num_bits {
+        return Err(EncodeError::UnexpectedEOF);
+    }
+    for bit in 0..num_bits {
+        let src_bit = src[bit / 8] & (0x80 >> (bit % 8)) != 0;
+        let tar = bit_offset + bit;
+        let mask = 0x80 >> (tar % 8);
+        if src_bit {
+            buf[tar / 8] |= mask;
+        } else {
+            buf[tar / 8] &= !mask;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Adapters to decode instructions from a `bytes::Buf` and to encode them into a
+//! `bytes::BufMut`. This module is only available with the `bytes` feature.
+use crate::{DecodeError, EncodeError, InstructionSet};
+use bytes::{Buf, BufMut};
+use std::io::IoSlice;
+
+/// The number of chunks `decode_buf(..)` asks for first if an instruction spans several chunks.
+/// It asks for twice as many until it got all of them.
+const INITIAL_CHUNKS: usize = 16;
+
+/// Decodes the next instruction from `buf` and advances it past the instruction. Returns the
+/// number of bytes read and the instruction. If `buf` doesn't hold the whole instruction
+/// `DecodeError::UnexpectedEOF` is returned and `buf` is left untouched, so it can be called again
+/// once more bytes arrived.
+///
+/// An instruction that spans several chunks is copied from `Buf::chunks_vectored(..)`. If `buf`
+/// doesn't expose all its chunks that way (the default only exposes the first one), the chunks
+/// are copied from a clone of `buf` instead.
+pub fn decode_buf<I: InstructionSet, B: Buf + Clone>(
+    buf: &mut B,
+) -> Result<(usize, I), DecodeError> {
+    let res = match I::decode(buf.chunk()) {
+        //the instruction might continue in the next chunk
+        Err(DecodeError::UnexpectedEOF) if buf.chunk().len() < buf.remaining() => {
+            match copy_chunks(buf) {
+                Some(mem) => I::decode(&mem),
+                None => decode_chunks(buf.clone()),
+            }
+        }
+        res => res,
+    };
+    if let Ok((num_bytes, _)) = res {
+        buf.advance(num_bytes);
+    }
+    res
+}
+
+/// Copies the remaining bytes of `buf` without advancing it. Returns `None` if `buf` doesn't
+/// expose all of them through `Buf::chunks_vectored(..)`.
+fn copy_chunks<B: Buf>(buf: &B) -> Option<Vec<u8>> {
+    let mut num_chunks = INITIAL_CHUNKS;
+    loop {
+        let mut chunks = vec![IoSlice::new(&[]); num_chunks];
+        let filled = buf.chunks_vectored(&mut chunks);
+        let chunks = &chunks[..filled];
+        if chunks.iter().map(|chunk| chunk.len()).sum::<usize>() == buf.remaining() {
+            return Some(
+                chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.iter().copied())
+                    .collect(),
+            );
+        }
+        if filled < num_chunks {
+            return None;
+        }
+        num_chunks *= 2;
+    }
+}
+
+/// Decodes the next instruction from `rest` by copying one chunk after the other until the
+/// decoder stops reporting `DecodeError::UnexpectedEOF`.
+fn decode_chunks<I: InstructionSet, B: Buf>(mut rest: B) -> Result<(usize, I), DecodeError> {
+    let mut mem = Vec::with_capacity(rest.chunk().len());
+    loop {
+        let chunk_len = rest.chunk().len();
+        mem.extend_from_slice(rest.chunk());
+        rest.advance(chunk_len);
+        match I::decode(&mem) {
+            Err(DecodeError::UnexpectedEOF) if rest.has_remaining() => continue,
+            res => return res,
+        }
+    }
+}
+
+/// Encodes `instr` and puts it into `buf`. Returns the number of bytes written. If `buf` can't
+/// hold the instruction `EncodeError::UnexpectedEOF` is returned and nothing is written.
+pub fn encode_buf<I: InstructionSet, B: BufMut>(
+    instr: &I,
+    buf: &mut B,
+) -> Result<usize, EncodeError> {
+    let mem = instr.to_vec()?;
+    if buf.remaining_mut() < mem.len() {
+        return Err(EncodeError::UnexpectedEOF);
+    }
+    buf.put_slice(&mem);
+    Ok(mem.len())
+}
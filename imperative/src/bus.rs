@@ -0,0 +1,138 @@
+//! Decoding from addressed memory instead of slices. Emulated machines rarely keep their code in
+//! one contiguous slice: ROM is banked, RAM is mirrored, addresses map to MMIO and the address
+//! space wraps around at its end. A `Bus` maps each address to a byte and
+//! `InstructionSet::decode_at(bus, addr)` fetches the bytes of one instruction from it.
+//! `InstructionSet::decode_split(first, second)` decodes from memory split into two slices (e.g. a
+//! ring buffer).
+use crate::{DecodeError, InstructionSet};
+
+/// The number of bytes of an instruction that are fetched onto the stack. Longer instructions
+/// (e.g. with large payloads) are fetched into a vector.
+const STACK_LEN: usize = 32;
+
+/// An address of a `Bus`. Adding an offset wraps around at the end of the address space, so an
+/// instruction at `0xffff` of a 16 bit bus continues at `0x0000`.
+pub trait Address: Copy {
+    /// The number of addresses or `usize::MAX` if there are more. No instruction is fetched past
+    /// this many bytes, since it would overlap itself.
+    const SIZE: usize;
+    /// Returns the address `offset` bytes after this one.
+    fn offset(self, offset: usize) -> Self;
+}
+
+macro_rules! impl_address {
+    ($($ty:ty),*) => {
+        $(
+            impl Address for $ty {
+                const SIZE: usize = match 1usize.checked_shl(<$ty>::BITS) {
+                    Some(size) => size,
+                    None => usize::MAX,
+                };
+
+                fn offset(self, offset: usize) -> Self {
+                    self.wrapping_add(offset as $ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_address!(u8, u16, u32, u64, usize);
+
+/// A memory that can be read one byte at a time.
+pub trait Bus<A: Address> {
+    /// Returns the byte at `addr` or `None` if there is none (e.g. past the end of a slice or in
+    /// an unmapped region). Decoding then fails with `DecodeError::UnexpectedEOF`.
+    fn read(&self, addr: A) -> Option<u8>;
+}
+
+impl<A: Address, F: Fn(A) -> Option<u8>> Bus<A> for F {
+    fn read(&self, addr: A) -> Option<u8> {
+        self(addr)
+    }
+}
+
+impl Bus<usize> for [u8] {
+    fn read(&self, addr: usize) -> Option<u8> {
+        self.get(addr).copied()
+    }
+}
+
+/// Decodes the instruction at `addr`. The first `I::MIN_LEN` bytes are fetched at once, since
+/// every instruction occupies at least as many. After that one byte is fetched each time the
+/// decoder reports `DecodeError::UnexpectedEOF`, so no byte after the end of the instruction is
+/// read. No more than `I::MAX_LEN` or `A::SIZE` bytes are fetched.
+/// `InstructionSet::decode_at(bus, addr)` calls this.
+pub fn decode_at<I, A, B>(bus: &B, addr: A) -> Result<(usize, I), DecodeError>
+where
+    I: InstructionSet,
+    A: Address,
+    B: Bus<A> + ?Sized,
+{
+    let end = I::MAX_LEN.map_or(A::SIZE, |max_len| max_len.min(A::SIZE));
+    decode_fetched(I::MIN_LEN, end, |idx| bus.read(addr.offset(idx)))
+}
+
+/// Decodes the instruction at the start of `first` which continues in `second` (e.g. the two
+/// halves of a ring buffer returned by `VecDeque::as_slices()`). If the instruction lies within
+/// one of the slices it is decoded from it directly. Only an instruction that straddles the end
+/// of `first` is fetched into a buffer, but no more than `I::MAX_LEN` bytes.
+/// `InstructionSet::decode_split(first, second)` calls this.
+pub fn decode_split<I: InstructionSet>(
+    first: &[u8],
+    second: &[u8],
+) -> Result<(usize, I), DecodeError> {
+    if first.is_empty() {
+        return I::decode(second);
+    }
+    match I::decode(first) {
+        Err(DecodeError::UnexpectedEOF) if !second.is_empty() => {
+            //the instruction is longer than `first`
+            let len = first.len() + second.len();
+            decode_fetched(
+                I::MIN_LEN.max(first.len() + 1),
+                I::MAX_LEN.map_or(len, |max_len| max_len.min(len)),
+                |idx| match first.get(idx) {
+                    Some(byte) => Some(*byte),
+                    None => second.get(idx - first.len()).copied(),
+                },
+            )
+        }
+        res => res,
+    }
+}
+
+/// Fetches the bytes `fetch(0)..fetch(num_bytes)` until they hold an instruction. `num_bytes`
+/// starts at `min_len` and grows by one each time the decoder reports
+/// `DecodeError::UnexpectedEOF`. No more than `end` bytes are fetched and a byte `fetch(..)`
+/// can't provide is reported as `DecodeError::UnexpectedEOF` as well.
+fn decode_fetched<I, F>(min_len: usize, end: usize, fetch: F) -> Result<(usize, I), DecodeError>
+where
+    I: InstructionSet,
+    F: Fn(usize) -> Option<u8>,
+{
+    let fetch = |idx| fetch(idx).ok_or(DecodeError::UnexpectedEOF);
+    let mut mem = [0u8; STACK_LEN];
+    let mut num_bytes = min_len.min(STACK_LEN).min(end);
+    for (idx, byte) in mem[..num_bytes].iter_mut().enumerate() {
+        *byte = fetch(idx)?;
+    }
+    loop {
+        match I::decode(&mem[..num_bytes]) {
+            Err(DecodeError::UnexpectedEOF) if num_bytes < STACK_LEN && num_bytes < end => {
+                mem[num_bytes] = fetch(num_bytes)?;
+                num_bytes += 1;
+            }
+            Err(DecodeError::UnexpectedEOF) if num_bytes < end => break,
+            res => return res,
+        }
+    }
+    let mut mem = mem.to_vec();
+    loop {
+        mem.push(fetch(mem.len())?);
+        match I::decode(&mem) {
+            Err(DecodeError::UnexpectedEOF) if mem.len() < end => continue,
+            res => return res,
+        }
+    }
+}
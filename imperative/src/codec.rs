@@ -0,0 +1,73 @@
+//! A `tokio_util::codec` implementation to read and write frames of a protocol defined with
+//! `InstructionSet`. This module is only available with the `tokio-util` feature.
+use crate::{DecodeError, InstructionSet};
+use bytes::{Buf, BytesMut};
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Decodes and encodes frames of the instruction set `I`. If the buffer ends within a frame, the
+/// decoder waits for more bytes. An unknown opcode or an invalid varint is reported as an error of
+/// kind `InvalidData` and an instruction that can't be encoded as an error of kind
+/// `InvalidInput`.
+#[derive(Debug)]
+pub struct InstructionCodec<I> {
+    instruction_set: PhantomData<fn() -> I>,
+}
+
+impl<I> InstructionCodec<I> {
+    /// Constructs a codec for the instruction set `I`.
+    pub fn new() -> Self {
+        InstructionCodec {
+            instruction_set: PhantomData,
+        }
+    }
+}
+
+impl<I> Default for InstructionCodec<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> Clone for InstructionCodec<I> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<I: InstructionSet> Decoder for InstructionCodec<I> {
+    type Item = I;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<I>, Error> {
+        match I::decode(src) {
+            Ok((num_bytes, instr)) => {
+                src.advance(num_bytes);
+                Ok(Some(instr))
+            }
+            Err(DecodeError::UnexpectedEOF) => Ok(None),
+            Err(err) => Err(Error::new(ErrorKind::InvalidData, err)),
+        }
+    }
+}
+
+impl<I: InstructionSet> Encoder<I> for InstructionCodec<I> {
+    type Error = Error;
+
+    fn encode(&mut self, instr: I, dst: &mut BytesMut) -> Result<(), Error> {
+        Encoder::<&I>::encode(self, &instr, dst)
+    }
+}
+
+impl<I: InstructionSet> Encoder<&I> for InstructionCodec<I> {
+    type Error = Error;
+
+    fn encode(&mut self, instr: &I, dst: &mut BytesMut) -> Result<(), Error> {
+        let mem = instr
+            .to_vec()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        dst.extend_from_slice(&mem);
+        Ok(())
+    }
+}
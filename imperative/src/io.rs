@@ -0,0 +1,67 @@
+//! Adapters to decode instructions from a `std::io::Read` and to encode them into a
+//! `std::io::Write`. Decoding and encoding errors are reported as `std::io::Error`s of kind
+//! `InvalidData` and `InvalidInput` respectively.
+//!
+//! `read_instruction(..)` reads one byte at a time until the decoder stops asking for more
+//! memory, so it never consumes a byte of the following instruction. Readers without their own
+//! buffer (e.g. a `File`) should be wrapped in a `std::io::BufReader`.
+use crate::{DecodeError, EncodeError, InstructionSet};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+/// The number of bytes `encode_to_vec(..)` tries first if no length is known. Most instructions
+/// fit into it.
+const INITIAL_BUF_LEN: usize = 16;
+
+/// Reads the next instruction from `reader`. Returns the number of bytes read and the
+/// instruction, or `None` if the reader ended before the first byte of an instruction. If it ends
+/// within an instruction an error of kind `UnexpectedEof` is returned.
+pub fn read_instruction<I: InstructionSet, R: Read>(reader: &mut R) -> Result<Option<(usize, I)>> {
+    let mut mem = Vec::with_capacity(INITIAL_BUF_LEN);
+    let mut byte = [0u8];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) if mem.is_empty() => return Ok(None),
+            Ok(0) => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    DecodeError::UnexpectedEOF,
+                ))
+            }
+            Ok(_) => mem.push(byte[0]),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+        match I::decode(&mem) {
+            Ok(res) => return Ok(Some(res)),
+            Err(DecodeError::UnexpectedEOF) => continue,
+            Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
+        }
+    }
+}
+
+/// Encodes `instr` and writes it to `writer`. Returns the number of bytes written.
+pub fn write_instruction<I: InstructionSet, W: Write>(instr: &I, writer: &mut W) -> Result<usize> {
+    let mem = instr
+        .to_vec()
+        .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+    writer.write_all(&mem)?;
+    Ok(mem.len())
+}
+
+/// Encodes `instr` into a vector of `len` bytes that is grown until the instruction fits.
+pub(crate) fn encode_to_vec<I: InstructionSet>(
+    instr: &I,
+    len: usize,
+) -> std::result::Result<Vec<u8>, EncodeError> {
+    let mut mem = vec![0u8; len];
+    loop {
+        match instr.encode(&mut mem) {
+            Ok(num_bytes) => {
+                mem.truncate(num_bytes);
+                return Ok(mem);
+            }
+            Err(EncodeError::UnexpectedEOF) => mem.resize((2 * mem.len()).max(INITIAL_BUF_LEN), 0),
+            Err(err) => return Err(err),
+        }
+    }
+}
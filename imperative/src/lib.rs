@@ -38,8 +38,635 @@
 //!    assert_eq!([0x2a, 0xbc, 0xde, 0xf0], mem[100..104])
 //!}
 //!```
+//!
+//! # Byte order
+//! Variables are assembled from the opcode with the leftmost bit as most significant bit. Many
+//! byte oriented instruction sets (e.g. 8080, Z80 and 6502) store immediates little endian
+//! instead. A variable marked with `#[endian = "little"]` is split into groups of 8 bits and the
+//! first group in the opcode becomes the least significant byte.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum I8080 {
+//!    #[opcode = "0xc3_nnnn"]
+//!    Jmp{
+//!        #[endian = "little"]
+//!        n:u16},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((3, I8080::Jmp{n:0x1234})), I8080::decode(&[0xc3, 0x34, 0x12]));
+//!}
+//!```
+//!
+//! # Word memories
+//! With `#[imperative(word = "u16")]` (or `"u32"`, `"u64"`) the instruction set additionally
+//! implements `WordInstructionSet<u16>` which decodes from `&[u16]` and compares whole words. The
+//! opcodes are split into words from left to right, so each opcode needs to be a multiple of the
+//! word size. `#[operand]` fields are decoded from the following words. Every `InstructionSet`
+//! also implements `WordInstructionSet<u8>`, so the word type of the memory needs to be known
+//! (e.g. `&[0x4303u16]` instead of `&[0x4303]`).
+//!```rust
+//! use imperative_rs::{InstructionSet, WordInstructionSet};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(word = "u16")]
+//!enum Msp430 {
+//!    #[opcode = "0x4303"]
+//!    Nop,
+//!    #[opcode = "0b001111_oooooooooo"]
+//!    Jmp{o:u16},
+//!}
+//!
+//!fn main() {
+//!    let mem: Vec<u16> = vec![0x3c05, 0x4303];
+//!    assert_eq!(Ok((1, Msp430::Jmp{o:5})), Msp430::decode_words(&mem));
+//!    assert_eq!(Ok((1, Msp430::Nop)), Msp430::decode_words(&mem[1..]));
+//!}
+//!```
+//!
+//! Machines whose word width is not a multiple of 8 (e.g. PDP-8 with 12, PDP-1 with 18 or PDP-10
+//! with 36 bits) additionally declare the width with `word_bits`. Each word of memory then holds
+//! one instruction word in its lower bits, the upper bits are ignored when decoding and cleared
+//! when encoding. Opcodes are not padded in this case and have to consist of whole instruction
+//! words. `InstructionSet::decode(..)` stores each instruction word in whole bytes (e.g. 12 bit
+//! words in two bytes).
+//!```rust
+//! use imperative_rs::{InstructionSet, WordInstructionSet};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(word = "u16", word_bits = 12)]
+//!enum Pdp8 {
+//!    #[opcode = "0xf02"]
+//!    Hlt,
+//!    #[opcode = "0b101_i_z_mmmmmmm"]
+//!    Jmp{i:bool, z:bool, m:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((1, Pdp8::Hlt)), Pdp8::decode_words(&[0o7402u16]));
+//!    assert_eq!(Ok((2, Pdp8::Hlt)), Pdp8::decode(&[0x0f, 0x02]));
+//!}
+//!```
+//!
+//! # Cycle counts
+//! Each variant can be annotated with the number of cycles it takes, either as a constant
+//! (`#[cycles = 4]`) or as an expression over the variant's fields (`#[cycles = "4 + x"]`).
+//! If one variant is annotated, all of them need to be. The derive then generates
+//! `fn cycles(&self) -> u32`.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet)]
+//!enum Is {
+//!    #[opcode = "0x00"]
+//!    #[cycles = 2]
+//!    Nop,
+//!    #[opcode = "0b0001000p_nnnnnnnn"]
+//!    #[cycles = "3 + page_cross as u32"]
+//!    Jmp{
+//!        #[variable = "p"]
+//!        page_cross:bool,
+//!        n:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(2, Is::Nop.cycles());
+//!    assert_eq!(4, Is::Jmp{page_cross:true, n:0x12}.cycles());
+//!}
+//!```
+//!
+//! # Decode modes
+//! Some instruction sets decode differently depending on the state of the CPU. The type of this
+//! state is defined with `#[imperative(context = "..")]` on the `enum`. Variants annotated with
+//! `#[when = ".."]` are only decoded by the generated `decode_with(mem, ctx)` when the condition
+//! holds for `ctx`. `InstructionSet::decode(..)` only decodes variants without a condition.
+//! Opcodes only need to be unique among the variants that can be decoded in the same mode.
+//! Conditions may overlap: every condition that holds is tried. If the variants of two conditions
+//! that hold share an opcode, the condition that appears first in the `enum` takes precedence.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(PartialEq)]
+//!enum Mode {
+//!    Arm,
+//!    Thumb,
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(context = "Mode")]
+//!enum Is {
+//!    #[opcode = "0x0x"]
+//!    #[when = "*ctx == Mode::Arm"]
+//!    Add{x:u8},
+//!    #[opcode = "0x0x"]
+//!    #[when = "*ctx == Mode::Thumb"]
+//!    Sub{x:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((1, Is::Add{x:3})), Is::decode_with(&[0x03], &Mode::Arm));
+//!    assert_eq!(Ok((1, Is::Sub{x:3})), Is::decode_with(&[0x03], &Mode::Thumb));
+//!}
+//!```
+//!
+//! # Extensions
+//! Variants can belong to an extension of the instruction set (`#[extension = "M"]`). The derive
+//! generates a constant `EXT_M` of type `ExtSet` for each extension and
+//! `decode_for(mem, enabled)`, which treats the opcodes of disabled extensions as unknown.
+//! `InstructionSet::decode(..)` decodes all extensions.
+//!```rust
+//! use imperative_rs::{DecodeError, ExtSet, InstructionSet};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Is {
+//!    #[opcode = "0x0x"]
+//!    Add{x:u8},
+//!    #[opcode = "0x1x"]
+//!    #[extension = "M"]
+//!    Mul{x:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((1, Is::Mul{x:3})), Is::decode_for(&[0x13], Is::EXT_M));
+//!    assert_eq!(Err(DecodeError::UnknownOpcode), Is::decode_for(&[0x13], ExtSet::empty()));
+//!}
+//!```
+//!
+//! # Operands
+//! Fields marked with `#[operand]` are not encoded in the opcode. Their type has to implement
+//! `InstructionSet` itself and they are decoded from the bytes following the opcode in order of
+//! declaration. Their length is added to the length of the instruction. This way addressing modes
+//! (e.g. x86 ModRM bytes) can be defined once and reused by many instructions.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum ModRm {
+//!    #[opcode = "0b11rrrmmm"]
+//!    Reg{r:u8, m:u8},
+//!    #[opcode = "0b01rrrmmm_iiiiiiii"]
+//!    Disp{r:u8, m:u8, i:i8},
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Is {
+//!    #[opcode = "0x01"]
+//!    Add{
+//!        #[operand]
+//!        modrm:ModRm},
+//!}
+//!
+//!fn main() {
+//!    let (num_bytes, instr) = Is::decode(&[0x01, 0b01_000_001, 0x10]).unwrap();
+//!    assert_eq!(3, num_bytes);
+//!    assert_eq!(Is::Add{modrm:ModRm::Disp{r:0, m:1, i:0x10}}, instr);
+//!}
+//!```
+//!
+//! # Prefixes and opcode pages
+//! Prefixes that select a different opcode table (e.g. the Z80 `0xcb` and `0xed` pages) are
+//! variants whose only field is an `#[operand]` containing the instruction set of that page. This
+//! keeps each page small and the prefix visible in the decoded instruction. Prefixes that modify
+//! the following instruction (e.g. x86 `0x66` or `0xf3`) are decoded with `Prefixed<P, I>`.
+//!```rust
+//! use imperative_rs::{InstructionSet, Prefixed};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum CbPage {
+//!    #[opcode = "0b00000rrr"]
+//!    Rlc{r:u8},
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Z80 {
+//!    #[opcode = "0x00"]
+//!    Nop,
+//!    #[opcode = "0xcb"]
+//!    Cb{
+//!        #[operand]
+//!        op:CbPage},
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Prefix {
+//!    #[opcode = "0x66"]
+//!    OperandSize,
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((2, Z80::Cb{op:CbPage::Rlc{r:7}})), Z80::decode(&[0xcb, 0x07]));
+//!    let (num_bytes, instr) = Prefixed::<Prefix, Z80>::decode(&[0x66, 0x00]).unwrap();
+//!    assert_eq!(2, num_bytes);
+//!    assert_eq!(vec![Prefix::OperandSize], instr.prefixes);
+//!    assert_eq!(Z80::Nop, instr.instruction);
+//!}
+//!```
+//!
+//! # Payloads
+//! A `Vec<u8>` field marked with `#[len = ".."]` is a payload following the opcode. The
+//! expression gives its length in bytes and can use the other fields of the instruction. Since
+//! the decoded instruction can't borrow from the memory, payloads are copied.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Evm {
+//!    #[opcode = "0b011nnnnn"]
+//!    Push{
+//!        n:u8,
+//!        #[len = "n + 1"]
+//!        data:Vec<u8>},
+//!}
+//!
+//!fn main() {
+//!    let (num_bytes, instr) = Evm::decode(&[0x61, 0xab, 0xcd]).unwrap();
+//!    assert_eq!(3, num_bytes);
+//!    assert_eq!(Evm::Push{n:1, data:vec![0xab, 0xcd]}, instr);
+//!}
+//!```
+//!
+//! # Variable length integers
+//! A field marked with `#[varint = ".."]` follows the opcode as a LEB128 encoded integer.
+//! Supported encodings are `uleb128` for unsigned fields and `sleb128` and `zigzag` for signed
+//! fields (see the `varint` module). Overlong encodings are rejected with
+//! `DecodeError::Overlong`, values that don't fit into the field with `DecodeError::Overflow`.
+//! Varint fields can be used by `#[len = ".."]` expressions.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Wasm {
+//!    #[opcode = "0x41"]
+//!    I32Const{
+//!        #[varint = "sleb128"]
+//!        value:i32},
+//!    #[opcode = "0x0e"]
+//!    BrTable{
+//!        #[varint = "uleb128"]
+//!        count:u32,
+//!        #[len = "count"]
+//!        labels:Vec<u8>},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((3, Wasm::I32Const{value:-200})), Wasm::decode(&[0x41, 0xb8, 0x7e]));
+//!    let mut buf = [0; 8];
+//!    let instr = Wasm::BrTable{count:2, labels:vec![0, 1]};
+//!    assert_eq!(Ok(4), instr.encode(&mut buf));
+//!    assert_eq!([0x0e, 0x02, 0x00, 0x01], buf[..4]);
+//!}
+//!```
+//!
+//! # Bit streams
+//! Some formats pack instructions without aligning them to bytes (e.g. compressed bytecode).
+//! `#[imperative(bitstream)]` adds `decode_bits(mem, bit_offset)` and
+//! `encode_bits(buf, bit_offset)` which decode and encode an instruction starting at any bit of
+//! the memory. Bits are counted from the most significant bit of the first byte and the returned
+//! lengths are numbers of bits. Opcodes are not padded in a bit stream, so no opcode may be a
+//! prefix of another one. Operands need to be bit streams as well.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(bitstream)]
+//!enum Packed {
+//!    #[opcode = "0b00"]
+//!    Nop,
+//!    #[opcode = "0b01xxxx"]
+//!    Push{x:u8},
+//!    #[opcode = "0b1"]
+//!    Halt,
+//!}
+//!
+//!fn main() {
+//!    let mem = [0b0101_0100, 0b1000_0000];
+//!    assert_eq!(Ok((6, Packed::Push{x:5})), Packed::decode_bits(&mem, 0));
+//!    assert_eq!(Ok((2, Packed::Nop)), Packed::decode_bits(&mem, 6));
+//!    assert_eq!(Ok((1, Packed::Halt)), Packed::decode_bits(&mem, 8));
+//!    let mut buf = [0; 2];
+//!    assert_eq!(Ok(6), Packed::Push{x:5}.encode_bits(&mut buf, 0));
+//!    assert_eq!(Ok(2), Packed::Nop.encode_bits(&mut buf, 6));
+//!    assert_eq!(Ok(1), Packed::Halt.encode_bits(&mut buf, 8));
+//!    assert_eq!(mem, buf);
+//!}
+//!```
+//!
+//! # Bundles
+//! VLIW instruction sets (e.g. Itanium bundles or Hexagon packets) group several instructions
+//! into one bundle. A field marked with `#[slot]` is named like a variable but holds an
+//! instruction of its own. Its bits are collected from the opcode in order and decoded with
+//! `decode_bits(..)` of the field's type, which therefore needs `#[imperative(bitstream)]`. The
+//! constant bits of the opcode (e.g. a template) select the variant and with it the types of the
+//! slots. The bits of a slot don't need to be consecutive, so bits like the parse bits of a
+//! Hexagon packet can be skipped. An instruction in a slot can't be longer than the slot and bits
+//! of the slot it doesn't use are encoded as zero.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(bitstream)]
+//!enum Alu {
+//!    #[opcode = "0b0xxxyyy"]
+//!    Add{x:u8, y:u8},
+//!    #[opcode = "0b1xxxxxx"]
+//!    Li{x:u8},
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(bitstream)]
+//!enum Mem {
+//!    #[opcode = "0b0xxxxxx"]
+//!    Load{x:u8},
+//!    #[opcode = "0b1xxxxxx"]
+//!    Store{x:u8},
+//!}
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Bundle {
+//!    #[opcode = "0b00sssssssttttttt"]
+//!    AluAlu{#[slot] s:Alu, #[slot] t:Alu},
+//!    #[opcode = "0b01sssssssttttttt"]
+//!    AluMem{#[slot] s:Alu, #[slot] t:Mem},
+//!}
+//!
+//!fn main() {
+//!    let bundle = Bundle::AluMem{s:Alu::Add{x:3, y:5}, t:Mem::Store{x:9}};
+//!    assert_eq!(Ok((2, bundle)), Bundle::decode(&[0b0100_1110, 0b1100_1001]));
+//!}
+//!```
+//!
+//! # Instruction lengths
+//! `instruction_len(mem)` returns the number of bytes `InstructionSet::decode(..)` would report
+//! but only matches the opcode and skips the fields. Variables are only decoded if the length of
+//! a payload depends on them. This makes linear sweeps over variable length code cheap. The
+//! constants `MIN_LEN` and `MAX_LEN` give the bounds of the instruction lengths. `MAX_LEN` is
+//! `None` if an instruction has a payload. All three are part of the `InstructionSet` trait, so
+//! operands can also be hand written instruction sets or `Prefixed<P, I>`, whose defaults decode
+//! the instruction to find its length.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Evm {
+//!    #[opcode = "0x00"]
+//!    Stop,
+//!    #[opcode = "0b011nnnnn"]
+//!    Push{n:u8, #[len = "n + 1"] data:Vec<u8>},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok(3), Evm::instruction_len(&[0x61, 0xab, 0xcd]));
+//!    assert_eq!(1, Evm::MIN_LEN);
+//!    assert_eq!(None, Evm::MAX_LEN);
+//!}
+//!```
+//!
+//! Encoded instructions can be measured without a buffer. `encoded_len(&self)` returns the number
+//! of bytes `InstructionSet::encode(..)` writes, so assemblers can lay out code before encoding
+//! it. `to_vec(&self)` encodes the instruction into a vector of exactly that many bytes. Both are
+//! part of the `InstructionSet` trait. Instruction sets without payloads also get `to_bytes(&self)`
+//! which encodes into an array of `MAX_ENCODED_LEN` bytes on the stack and returns it with the
+//! length of the instruction.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Chip8 {
+//!    #[opcode = "0x00e0"]
+//!    Cls,
+//!    #[opcode = "0x1nnn"]
+//!    Jmp{n:u16},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(2, Chip8::Cls.encoded_len());
+//!    assert_eq!(Ok(vec![0x12, 0x34]), Chip8::Jmp{n:0x234}.to_vec());
+//!    assert_eq!(2, Chip8::MAX_ENCODED_LEN);
+//!    assert_eq!(Ok(([0x12, 0x34], 2)), Chip8::Jmp{n:0x234}.to_bytes());
+//!}
+//!```
+//!
+//! # Instruction kinds
+//! `#[imperative(kind = "EvmKind")]` generates a fieldless enum `EvmKind` with one variant per
+//! instruction (e.g. for instruction mix histograms or coverage counters). It implements `Copy`,
+//! `Hash` and `Ord` and `EvmKind::ALL` lists all kinds in order of declaration.
+//! `classify(mem)` returns the length and the kind of an instruction like `instruction_len(..)`
+//! and `kind(&self)` returns the kind of a decoded instruction.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(kind = "EvmKind")]
+//!enum Evm {
+//!    #[opcode = "0x00"]
+//!    Stop,
+//!    #[opcode = "0b011nnnnn"]
+//!    Push{n:u8, #[len = "n + 1"] data:Vec<u8>},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((3, EvmKind::Push)), Evm::classify(&[0x61, 0xab, 0xcd]));
+//!    assert_eq!(EvmKind::Stop, Evm::Stop.kind());
+//!    assert_eq!(&[EvmKind::Stop, EvmKind::Push], EvmKind::ALL);
+//!}
+//!```
+//!
+//! # Decoder backends
+//! By default the decoder is a tree that tests one bit of the opcode at a time. With
+//! `#[imperative(decoder = "table")]` the decoder instead matches a byte (or word) on all bits
+//! that tell the remaining instructions apart at once. The compiler turns these matches into
+//! jump tables which saves a lot of branches for instruction sets with many instructions (see the
+//! chip8 benchmark in `imperative-bench`). Both backends decode the same instructions.
+//!
+//! Independent of the backend, opcodes of two to sixteen bytes are loaded from memory as a single
+//! big endian integer, so the constant bits are compared and the variables are extracted with a
+//! few shifts and masks instead of byte by byte.
+//!
+//! Instructions with variables in the bits that tell the other instructions apart are part of
+//! multiple arms of the decoder. Arms for the same instructions are only generated once and
+//! shared, but large instruction sets can still lead to huge decoders. Each decoder is limited to
+//! 65536 match arms, which can be changed with `#[imperative(max_arms = N)]`. When the table
+//! backend finds no bits that are constant in all instructions, it matches on fields that are
+//! variable in a few of them instead.
+//!
+//! Instructions that are reached from multiple arms are decoded by a copy of their decoder in
+//! each arm. With `#[imperative(optimize = "size")]` the decoder of each instruction is generated
+//! only once and called from all of its arms, which saves code size on flash constrained targets.
+//! The `size` benchmark in `imperative-bench` reports the size of the chip8 decoders in both
+//! modes.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!#[imperative(decoder = "table")]
+//!enum Chip8 {
+//!    #[opcode = "0x00e0"]
+//!    Cls,
+//!    #[opcode = "0x1nnn"]
+//!    Jmp{n:u16},
+//!    #[opcode = "0x6xrr"]
+//!    Mov{x:u8, r:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((2, Chip8::Mov{x:1, r:0x23})), Chip8::decode(&[0x61, 0x23]));
+//!}
+//!```
+//!
+//! # Instruction weights
+//! The decoder is built to split the instructions into halves at every branch. If some
+//! instructions are executed far more often than others, they can be given a weight with
+//! `#[weight = N]` (the default is 1). The branches then split the instructions by their weight,
+//! so the heaviest instructions are decoded with the fewest branches. Emulators usually already
+//! have an execution histogram which can be used as it is with
+//! `#[imperative(profile = "cpu.profile")]`. The path is relative to the manifest of the crate
+//! and each line of the file holds the name of an instruction and its weight (e.g. `Mov 1500` or
+//! `Mov, 1500`). Lines starting with `#` are ignored and `#[weight = N]` takes precedence over the
+//! profile. Weights never change which instructions are decoded.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Chip8 {
+//!    #[opcode = "0x00e0"]
+//!    #[weight = 2]
+//!    Cls,
+//!    #[opcode = "0x1nnn"]
+//!    #[weight = 300]
+//!    Jmp{n:u16},
+//!    #[opcode = "0x6xrr"]
+//!    #[weight = 1500]
+//!    Mov{x:u8, r:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((2, Chip8::Mov{x:1, r:0x23})), Chip8::decode(&[0x61, 0x23]));
+//!}
+//!```
+//!
+//! # Unchecked decoding
+//! The decoder checks the length of the memory whenever it reads a byte that might be missing.
+//! If it is already known that the memory holds the longest opcode (e.g. because a block of code
+//! has been validated before), `unsafe fn decode_unchecked(mem)` leaves out these checks. Its
+//! safe counterpart `decode_padded(mem)` takes an array of `MAX_OPCODE_LEN` bytes rather than
+//! `MAX_LEN`, since operands, payloads and varints following the opcode are still checked. Don't
+//! pad a memory that might end within an instruction to fit the array: the padding would be
+//! decoded as the rest of the opcode instead of reporting `DecodeError::UnexpectedEOF`.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Isa {
+//!    #[opcode = "0x0x"]
+//!    Short{x:u8},
+//!    #[opcode = "0x1x_yy"]
+//!    Long{x:u8, y:u8},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(2, Isa::MAX_OPCODE_LEN);
+//!    assert_eq!(Ok((1, Isa::Short{x:2})), Isa::decode_padded(&[0x02, 0x00]));
+//!    let mem = [0x13, 0x45, 0x67];
+//!    assert_eq!(Ok((2, Isa::Long{x:3, y:0x45})), unsafe { Isa::decode_unchecked(&mem) });
+//!}
+//!```
+//!
+//! # Memory buses
+//! Emulators often don't have their code in one contiguous slice. `decode_at(bus, addr)` decodes
+//! the instruction at `addr` of a `bus::Bus`, which maps each address to a byte (e.g. to handle
+//! banked ROM or MMIO). Closures `Fn(addr) -> Option<u8>` and byte slices are buses as well. Only
+//! the bytes of the instruction are read and addresses wrap around at the end of the address
+//! space. Reading past the end of a slice or an address the bus returns `None` for is reported as
+//! `DecodeError::UnexpectedEOF`.
+//!```rust
+//! use imperative_rs::{DecodeError, InstructionSet};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Isa {
+//!    #[opcode = "0xea"]
+//!    Nop,
+//!    #[opcode = "0x4c_xx_xx"]
+//!    Jmp{x:u16},
+//!}
+//!
+//!fn main() {
+//!    let mut ram = vec![0xea; 0x10000];
+//!    ram[0xffff] = 0x4c;
+//!    ram[0x0000] = 0x12;
+//!    ram[0x0001] = 0x34;
+//!    let bus = |addr: u16| Some(ram[addr as usize]);
+//!    assert_eq!(Ok((3, Isa::Jmp{x:0x1234})), Isa::decode_at(&bus, 0xffff));
+//!    assert_eq!(Ok((1, Isa::Nop)), Isa::decode_at(&ram[..], 2));
+//!    assert_eq!(Err(DecodeError::UnexpectedEOF), Isa::decode_at(&ram[..], 0xffff));
+//!}
+//!```
+//!
+//! Memory split into two slices (e.g. the halves of a ring buffer returned by
+//! `VecDeque::as_slices()`) is decoded by `decode_split(first, second)`. Instructions within one
+//! of the slices are decoded in place and only an instruction that straddles the end of `first`
+//! is copied.
+//!```rust
+//! use imperative_rs::InstructionSet;
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Isa {
+//!    #[opcode = "0xea"]
+//!    Nop,
+//!    #[opcode = "0x4c_xx_xx"]
+//!    Jmp{x:u16},
+//!}
+//!
+//!fn main() {
+//!    assert_eq!(Ok((3, Isa::Jmp{x:0x1234})), Isa::decode_split(&[0x4c, 0x12], &[0x34, 0xea]));
+//!    assert_eq!(Ok((1, Isa::Nop)), Isa::decode_split(&[], &[0xea]));
+//!}
+//!```
+//!
+//! # Streams
+//! `io::read_instruction(reader)` decodes the next instruction from a `std::io::Read` and
+//! `io::write_instruction(&instr, writer)` encodes one into a `std::io::Write`. The reader is read
+//! until the decoder stops reporting `DecodeError::UnexpectedEOF`, so no byte of the following
+//! instruction is consumed. With the `bytes` feature `buf::decode_buf(buf)` and
+//! `buf::encode_buf(&instr, buf)` do the same for `bytes::Buf` and `bytes::BufMut`.
+//!
+//! The `tokio-util` feature adds `codec::InstructionCodec<I>` which implements the `Decoder` and
+//! `Encoder` traits of `tokio_util::codec`, so an instruction set can describe the frames of a
+//! binary protocol spoken over a socket or a serial port. A frame that isn't complete yet makes
+//! the decoder wait for more bytes while an unknown opcode is a framing error.
+//!```rust
+//! use imperative_rs::{io, InstructionSet};
+//!
+//!#[derive(InstructionSet, PartialEq, Debug)]
+//!enum Isa {
+//!    #[opcode = "0x0x"]
+//!    Short{x:u8},
+//!    #[opcode = "0x1x_yy"]
+//!    Long{x:u8, y:u8},
+//!}
+//!
+//!fn main() -> std::io::Result<()> {
+//!    let mut out = vec![];
+//!    io::write_instruction(&Isa::Long{x:2, y:0x34}, &mut out)?;
+//!    io::write_instruction(&Isa::Short{x:5}, &mut out)?;
+//!    let mut reader = &out[..];
+//!    assert_eq!(Some((2, Isa::Long{x:2, y:0x34})), io::read_instruction(&mut reader)?);
+//!    assert_eq!(Some((1, Isa::Short{x:5})), io::read_instruction(&mut reader)?);
+//!    assert_eq!(None, io::read_instruction::<Isa, _>(&mut reader)?);
+//!    Ok(())
+//!}
+//!```
 #[doc(hidden)]
 pub use imperative_rs_derive::*;
+pub mod bits;
+#[cfg(feature = "bytes")]
+pub mod buf;
+pub mod bus;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+pub mod io;
+pub mod varint;
 /// This type is returned by `fn InstructionSet::decode(...)` in case no instruction could be
 /// decoded.
 #[derive(Debug, PartialEq, PartialOrd)]
@@ -49,23 +676,104 @@ pub enum DecodeError {
     /// Is emitted if the slice ended before a complete opcode could be found. Extending the end
     /// of the slice could lead to successful decoding.
     UnexpectedEOF,
-    /// Is emitted when the target variable overflows during decoding.
-    /// Overflows should be caught at compiletime so encountering this error is currently a bug.
-    /// This might change in the future.
+    /// Is emitted when the target variable overflows during decoding. For fixed width variables
+    /// overflows are caught at compiletime, so this is only emitted for `#[varint = ".."]` fields.
     Overflow,
+    /// Is emitted if a `#[varint = ".."]` field is encoded with more bytes than necessary.
+    Overlong,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnknownOpcode => write!(f, "unknown opcode"),
+            DecodeError::UnexpectedEOF => write!(f, "memory ended within an instruction"),
+            DecodeError::Overflow => write!(f, "varint overflows its field"),
+            DecodeError::Overlong => write!(f, "varint is encoded with more bytes than necessary"),
+        }
+    }
 }
 
+impl std::error::Error for DecodeError {}
+
 /// This Type is returned by `fn InstructionSet::encode(...) -> {...}` when the instruction could not
 /// be encoded.
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum EncodeError {
     /// Instruction couldn't be encoded because the provided buffer was too short.
     UnexpectedEOF,
+    /// Instruction couldn't be encoded because the length of a payload (i.e. a field with
+    /// `#[len = ".."]`) doesn't match the length given by the other fields.
+    InvalidLength,
+}
+
+impl std::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::UnexpectedEOF => write!(f, "buffer is too short for the instruction"),
+            EncodeError::InvalidLength => write!(f, "payload length doesn't match its fields"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// A set of instruction set extensions. It is passed to the generated `decode_for(mem, enabled)`
+/// to decide which extensions can be decoded. For each `#[extension = "M"]` the derive generates a
+/// constant `EXT_M` containing only this extension, which can be combined with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ExtSet(u64);
+
+impl ExtSet {
+    /// The set without any extension.
+    pub const fn empty() -> Self {
+        ExtSet(0)
+    }
+
+    /// The set containing all extensions.
+    pub const fn all() -> Self {
+        ExtSet(u64::MAX)
+    }
+
+    /// Constructs a set from its bit representation. Each extension is represented by one bit.
+    pub const fn from_bits(bits: u64) -> Self {
+        ExtSet(bits)
+    }
+
+    /// Returns the bit representation of this set.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the set containing the extensions of both sets.
+    pub const fn union(self, other: Self) -> Self {
+        ExtSet(self.0 | other.0)
+    }
+
+    /// Returns true if all extensions of `other` are contained in this set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for ExtSet {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
 }
 
 /// This `trait` defines an instruction set. It provides functionality to decode from or encode to
 /// opcodes. It can be autoderived for suitable `enum`s by a procedual macro provided by this crate.
 pub trait InstructionSet: std::marker::Sized {
+    /// The least number of bytes an instruction of this instruction set occupies. The default is
+    /// `0`, which holds for every instruction set.
+    const MIN_LEN: usize = 0;
+    /// The largest number of bytes an instruction of this instruction set occupies or `None` if
+    /// the length of an instruction is not bounded (e.g. by a payload). The default is `None`,
+    /// which holds for every instruction set.
+    const MAX_LEN: Option<usize> = None;
     /// Used to decode an instruction (i.e. `Self`) from a byte buffer. The buffer needs to be
     /// provided as a `&[u8]` and the function returns a result containing either a tuple containing
     /// the number of bytes written and the resulting instruction or an `DecodeError`.
@@ -74,4 +782,160 @@ pub trait InstructionSet: std::marker::Sized {
     /// `&mut [u8]`. The function returns a result containing either the number of bytes read or an
     /// `EncodeError`
     fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError>;
+    /// Returns the number of bytes `decode(mem)` would report. The derived implementation only
+    /// matches the opcode and skips the fields. The default decodes the instruction.
+    fn instruction_len(mem: &[u8]) -> Result<usize, DecodeError> {
+        Self::decode(mem).map(|(num_bytes, _)| num_bytes)
+    }
+    /// Returns the number of bytes `encode(..)` writes for this instruction. The derived
+    /// implementation computes it from the fields.
+    ///
+    /// The default implementation encodes the instruction into a growing buffer. An instruction
+    /// that can't be encoded (e.g. because `encode(..)` reports an overflow) has no length, so 0 is
+    /// returned for it. Instruction sets whose encoding can fail should override this method.
+    fn encoded_len(&self) -> usize {
+        io::encode_to_vec(self, 0).map_or(0, |mem| mem.len())
+    }
+    /// Encodes the instruction into a vector of exactly its length. The vector starts with
+    /// `encoded_len()` bytes and is grown if that was too short, so only errors other than a too
+    /// short buffer are returned. Instruction sets without payloads also get an allocation free
+    /// `to_bytes(&self)`.
+    fn to_vec(&self) -> Result<Vec<u8>, EncodeError> {
+        io::encode_to_vec(self, self.encoded_len())
+    }
+    /// Decodes the instruction at `addr` of `bus` like `decode(..)`. Only the bytes of the
+    /// instruction are read from the bus and addresses wrap around at the end of the address
+    /// space. A byte the bus can't provide is reported as `DecodeError::UnexpectedEOF`.
+    fn decode_at<A, B>(bus: &B, addr: A) -> Result<(usize, Self), DecodeError>
+    where
+        A: bus::Address,
+        B: bus::Bus<A> + ?Sized,
+    {
+        bus::decode_at(bus, addr)
+    }
+    /// Decodes the instruction at the start of `first` which continues in `second` (e.g. the two
+    /// halves of a ring buffer) like `decode(..)`. Only an instruction that straddles the end of
+    /// `first` is copied.
+    fn decode_split(first: &[u8], second: &[u8]) -> Result<(usize, Self), DecodeError> {
+        bus::decode_split(first, second)
+    }
+}
+
+/// A unit of memory instructions can be decoded from. Word addressed machines (e.g. PDP-11 or
+/// MSP430) keep their memory as `Vec<u16>` or `Vec<u32>` which can be decoded without
+/// reinterpreting it as bytes.
+pub trait Word: Copy + std::fmt::Debug + PartialEq + Default {
+    /// The number of bits in one word.
+    const BITS: usize;
+}
+
+impl Word for u8 {
+    const BITS: usize = 8;
+}
+
+impl Word for u16 {
+    const BITS: usize = 16;
+}
+
+impl Word for u32 {
+    const BITS: usize = 32;
+}
+
+impl Word for u64 {
+    const BITS: usize = 64;
+}
+
+/// An instruction set that is decoded from and encoded into a memory of words `W`. It is derived
+/// for the word type given by `#[imperative(word = "u16")]`. Every `InstructionSet` is a
+/// `WordInstructionSet<u8>`, so code that is generic over the word type also works for bytes.
+pub trait WordInstructionSet<W: Word>: std::marker::Sized {
+    /// Decodes an instruction from a word buffer. Returns the number of words read and the
+    /// instruction or a `DecodeError`.
+    fn decode_words(mem: &[W]) -> Result<(usize, Self), DecodeError>;
+    /// Encodes the instruction into a word buffer. Returns the number of words written or an
+    /// `EncodeError`.
+    fn encode_words(&self, buf: &mut [W]) -> Result<usize, EncodeError>;
+}
+
+impl<T: InstructionSet> WordInstructionSet<u8> for T {
+    fn decode_words(mem: &[u8]) -> Result<(usize, Self), DecodeError> {
+        T::decode(mem)
+    }
+
+    fn encode_words(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        self.encode(buf)
+    }
+}
+
+/// An instruction preceded by any number of prefixes which modify it (e.g. the x86 operand size
+/// prefix `0x66` or `REX`). Both the prefixes and the instruction are instruction sets of their
+/// own. When decoding, prefixes are decoded until the memory doesn't contain a known prefix or a
+/// prefix occupies no bytes. Then the instruction itself is decoded.
+/// Prefixes that select a different opcode table (e.g. the Z80 `0xcb` page) are better modeled as
+/// a variant with an `#[operand]` field that contains the instruction set of that page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prefixed<P, I> {
+    /// The prefixes in the order they appear in memory.
+    pub prefixes: Vec<P>,
+    /// The instruction following the prefixes.
+    pub instruction: I,
+}
+
+impl<P: InstructionSet, I: InstructionSet> InstructionSet for Prefixed<P, I> {
+    //the number of prefixes is not bounded
+    const MIN_LEN: usize = I::MIN_LEN;
+
+    fn decode(mem: &[u8]) -> Result<(usize, Self), DecodeError> {
+        let mut num_bytes = 0;
+        let mut prefixes = vec![];
+        loop {
+            match P::decode(&mem[num_bytes..]) {
+                //a prefix without bytes would be decoded forever
+                Ok((0, _)) => break,
+                Ok((prefix_bytes, prefix)) => {
+                    num_bytes += prefix_bytes;
+                    prefixes.push(prefix);
+                }
+                Err(DecodeError::UnknownOpcode) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        let (instr_bytes, instruction) = I::decode(&mem[num_bytes..])?;
+        Ok((
+            num_bytes + instr_bytes,
+            Prefixed {
+                prefixes,
+                instruction,
+            },
+        ))
+    }
+
+    fn encode(&self, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let mut num_bytes = 0;
+        for prefix in &self.prefixes {
+            num_bytes += prefix.encode(&mut buf[num_bytes..])?;
+        }
+        num_bytes += self.instruction.encode(&mut buf[num_bytes..])?;
+        Ok(num_bytes)
+    }
+
+    fn instruction_len(mem: &[u8]) -> Result<usize, DecodeError> {
+        let mut num_bytes = 0;
+        loop {
+            match P::instruction_len(&mem[num_bytes..]) {
+                Ok(0) | Err(DecodeError::UnknownOpcode) => break,
+                Ok(prefix_bytes) => num_bytes += prefix_bytes,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(num_bytes + I::instruction_len(&mem[num_bytes..])?)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.prefixes
+            .iter()
+            .map(InstructionSet::encoded_len)
+            .sum::<usize>()
+            + self.instruction.encoded_len()
+    }
 }
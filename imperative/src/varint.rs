@@ -0,0 +1,131 @@
+//! Encoders and decoders for variable length integers as used by fields marked with
+//! `#[varint = ".."]`. All of them reject overlong encodings when decoding and produce the
+//! shortest encoding when encoding.
+//! * `uleb128` is unsigned LEB128 (e.g. WebAssembly, DWARF)
+//! * `sleb128` is signed LEB128
+//! * `zigzag` maps signed integers to unsigned ones (0, -1, 1, -2, ... => 0, 1, 2, 3, ...) before
+//!   encoding them as unsigned LEB128 (i.e. protobuf `sint`)
+use crate::{DecodeError, EncodeError};
+
+/// Decodes an unsigned LEB128 integer from the start of `mem`. Returns the number of bytes read
+/// and the value.
+pub fn decode_uleb128(mem: &[u8]) -> Result<(usize, u128), DecodeError> {
+    let mut value: u128 = 0;
+    for (idx, byte) in mem.iter().enumerate() {
+        let shift = 7 * idx;
+        let bits = (byte & 0x7f) as u128;
+        if shift >= 128 || (bits << shift) >> shift != bits {
+            return Err(DecodeError::Overflow);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            if idx > 0 && *byte == 0 {
+                return Err(DecodeError::Overlong);
+            }
+            return Ok((idx + 1, value));
+        }
+    }
+    Err(DecodeError::UnexpectedEOF)
+}
+
+/// Decodes a signed LEB128 integer from the start of `mem`. Returns the number of bytes read and
+/// the value.
+pub fn decode_sleb128(mem: &[u8]) -> Result<(usize, i128), DecodeError> {
+    let mut value: i128 = 0;
+    for (idx, byte) in mem.iter().enumerate() {
+        let shift = 7 * idx;
+        let bits = byte & 0x7f;
+        if shift >= 128 {
+            return Err(DecodeError::Overflow);
+        }
+        if shift + 7 > 128 {
+            //the bits that do not fit have to repeat the sign bit
+            let excess = bits >> (127 - shift);
+            if excess != 0 && excess != 0x7f >> (127 - shift) {
+                return Err(DecodeError::Overflow);
+            }
+        }
+        value |= (bits as i128) << shift;
+        if byte & 0x80 == 0 {
+            if idx > 0 {
+                //the last byte is redundant if it only repeats the sign of the byte before
+                let prev_sign = mem[idx - 1] & 0x40;
+                if (*byte == 0x00 && prev_sign == 0) || (*byte == 0x7f && prev_sign != 0) {
+                    return Err(DecodeError::Overlong);
+                }
+            }
+            let used_bits = shift + 7;
+            if used_bits < 128 {
+                value = (value << (128 - used_bits)) >> (128 - used_bits);
+            }
+            return Ok((idx + 1, value));
+        }
+    }
+    Err(DecodeError::UnexpectedEOF)
+}
+
+/// Decodes a zigzag encoded integer from the start of `mem`. Returns the number of bytes read and
+/// the value.
+pub fn decode_zigzag(mem: &[u8]) -> Result<(usize, i128), DecodeError> {
+    let (num_bytes, value) = decode_uleb128(mem)?;
+    Ok((num_bytes, (value >> 1) as i128 ^ -((value & 1) as i128)))
+}
+
+/// Encodes `value` as unsigned LEB128 into the start of `buf`. Returns the number of bytes
+/// written.
+pub fn encode_uleb128(mut value: u128, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let mut idx = 0;
+    loop {
+        if idx >= buf.len() {
+            return Err(EncodeError::UnexpectedEOF);
+        }
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[idx] = byte;
+            return Ok(idx + 1);
+        }
+        buf[idx] = byte | 0x80;
+        idx += 1;
+    }
+}
+
+/// Encodes `value` as signed LEB128 into the start of `buf`. Returns the number of bytes written.
+pub fn encode_sleb128(mut value: i128, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let mut idx = 0;
+    loop {
+        if idx >= buf.len() {
+            return Err(EncodeError::UnexpectedEOF);
+        }
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            buf[idx] = byte;
+            return Ok(idx + 1);
+        }
+        buf[idx] = byte | 0x80;
+        idx += 1;
+    }
+}
+
+/// Encodes `value` zigzag encoded into the start of `buf`. Returns the number of bytes written.
+pub fn encode_zigzag(value: i128, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    encode_uleb128(((value << 1) ^ (value >> 127)) as u128, buf)
+}
+
+/// Returns the number of bytes `encode_uleb128(value, ..)` writes.
+pub fn uleb128_len(value: u128) -> usize {
+    (128 - value.leading_zeros() as usize).div_ceil(7).max(1)
+}
+
+/// Returns the number of bytes `encode_sleb128(value, ..)` writes.
+pub fn sleb128_len(value: i128) -> usize {
+    //one more bit than the magnitude is needed for the sign
+    let magnitude = if value < 0 { !value } else { value };
+    (129 - magnitude.leading_zeros() as usize).div_ceil(7)
+}
+
+/// Returns the number of bytes `encode_zigzag(value, ..)` writes.
+pub fn zigzag_len(value: i128) -> usize {
+    uleb128_len(((value << 1) ^ (value >> 127)) as u128)
+}